@@ -1,16 +1,28 @@
 use anyhow::{bail, Context, Result};
+use bytes::Bytes;
 use clap::{Parser, ValueEnum};
 use colored::*;
 use diffx_core::{
-    diff, diff_with_config, parse_csv, parse_ini, parse_xml, value_type_name, DiffConfig,
-    DiffResult,
+    align_xml_repeated_elements, anchor_for_path, detect_binary_format, diff_columnar,
+    diff_documents, diff_with_config, document_label, document_path, escape_reserved_key,
+    evaluate_jsonnet, format_value, parse_arrow_ipc, parse_bson_with_options,
+    parse_cbor_with_options, parse_csv_with_options, parse_dotenv, parse_front_matter, parse_hcl,
+    parse_ini_with_options, parse_json, parse_json5, parse_jsonc, parse_msgpack_with_options,
+    parse_parquet, parse_plist_with_options, parse_properties_with_options, parse_protobuf,
+    parse_ron, parse_spreadsheet, parse_sqlite, parse_toml, parse_xml_with_options, parse_yaml,
+    parse_yaml_documents, read_arrow_ipc, read_decompressed, read_parquet, sniff_text_format,
+    sqlite_row_keys, to_plain_json, to_yaml_value, value_type_name, xml_diff_paths,
+    yaml_anchor_sources, BinaryFormat, BinaryOptions, ColumnarOptions, ColumnarRows, Compression,
+    CsvOptions, DiffConfig, DiffResult, FrontMatterOptions, IniOptions, JsonnetOptions,
+    PropertiesOptions, ProtobufEncoding, ProtobufOptions, SpreadsheetOptions, TextFormat,
+    XmlOptions,
 };
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::io::{self, Read};
+use std::io;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 use walkdir::WalkDir;
@@ -30,6 +42,94 @@ struct Args {
     #[arg(short, long, value_enum)]
     format: Option<Format>,
 
+    /// Accept comments and trailing commas in JSON input (e.g. tsconfig.json)
+    #[arg(long)]
+    lenient_json: bool,
+
+    /// Expand dotted .properties keys (e.g. "spring.datasource.url") into nested objects
+    #[arg(long)]
+    expand_dotted_keys: bool,
+
+    /// Read .properties values such as "8080" or "true" as numbers and booleans
+    #[arg(long)]
+    coerce_scalars: bool,
+
+    /// Represent every XML child element as an array, even when it occurs once
+    #[arg(long)]
+    xml_arrays: bool,
+
+    /// CSV field delimiter (a single character, or "tab"; default "," and tab for .tsv)
+    #[arg(long, value_name = "CHAR", value_parser = parse_csv_char)]
+    csv_delimiter: Option<u8>,
+
+    /// CSV quote character
+    #[arg(long, value_name = "CHAR", value_parser = parse_csv_char, default_value = "\"")]
+    csv_quote: u8,
+
+    /// Read CSV quote characters as ordinary text
+    #[arg(long)]
+    csv_no_quoting: bool,
+
+    /// CSV files have no header row; rows are compared as arrays of fields
+    #[arg(long)]
+    csv_no_headers: bool,
+
+    /// Match CSV, Parquet, Arrow and spreadsheet rows by these comma-separated key columns
+    /// instead of by position, as --array-id-key does (e.g., "id" reports
+    /// "rows[id=42].price"; 0-based indexes with --csv-no-headers)
+    #[arg(long, visible_alias = "key-columns", value_name = "COLUMNS")]
+    csv_key: Option<String>,
+
+    /// Compare the text of spreadsheet formulas instead of their cached values
+    #[arg(long)]
+    compare_formulas: bool,
+
+    /// Keep the case of INI section and key names instead of lowercasing them
+    #[arg(long)]
+    ini_case_sensitive: bool,
+
+    /// Keep INI keys that appear before the first section at the top level
+    /// instead of in a "default" section
+    #[arg(long)]
+    ini_root_globals: bool,
+
+    /// Collect the values of repeated INI keys into arrays instead of keeping the last one
+    #[arg(long)]
+    ini_duplicates_as_arrays: bool,
+
+    /// Characters that start an inline INI comment after a value (default ";#", "" disables)
+    #[arg(long, value_name = "CHARS")]
+    ini_inline_comments: Option<String>,
+
+    /// Reject INI lines that have a key but no "=" or ":"
+    #[arg(long)]
+    ini_require_values: bool,
+
+    /// Library search path for Jsonnet imports (repeatable)
+    #[arg(long, value_name = "DIR")]
+    jsonnet_jpath: Vec<PathBuf>,
+
+    /// External string variable for Jsonnet std.extVar (repeatable)
+    #[arg(long, value_name = "NAME=VALUE", value_parser = parse_ext_str)]
+    ext_str: Vec<(String, String)>,
+
+    /// Protobuf FileDescriptorSet describing the message type (protoc --descriptor_set_out)
+    #[arg(long, value_name = "FILE")]
+    proto_descriptor: Option<PathBuf>,
+
+    /// Fully qualified protobuf message type of the inputs (e.g., "shop.v1.Order")
+    #[arg(long, value_name = "NAME")]
+    proto_message: Option<String>,
+
+    /// Also compare the body of Markdown files after the front matter, as one string
+    #[arg(long)]
+    front_matter_body: bool,
+
+    /// Show binary blobs in MessagePack, CBOR, BSON, plist and SQLite input as hex instead of
+    /// base64
+    #[arg(long)]
+    bytes_as_hex: bool,
+
     /// Output format
     #[arg(short, long, value_enum)]
     output: Option<OutputFormat>,
@@ -46,14 +146,45 @@ struct Args {
     #[arg(long)]
     ignore_keys_regex: Option<String>,
 
+    /// Decode JSON or YAML held in string values before comparing, everywhere or only
+    /// at paths matching a regular expression (e.g., "^data\\.")
+    #[arg(
+        long,
+        value_name = "REGEX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ""
+    )]
+    decode_embedded: Option<String>,
+
+    /// Decode base64 string values at paths matching a regular expression (e.g.,
+    /// "^data\\.") before comparing; binary data only where it matches the whole path.
+    /// Decoded text is redacted unless --show-secrets is given
+    #[arg(long, value_name = "REGEX")]
+    decode_base64: Option<String>,
+
+    /// Print text decoded by --decode-base64 in cleartext
+    #[arg(long)]
+    show_secrets: bool,
+
     /// Tolerance for float comparisons (e.g., "0.001")
     #[arg(long)]
     epsilon: Option<f64>,
 
-    /// Key to use for identifying array elements (e.g., "id")
+    /// Key to use for identifying array elements (e.g., "id", or "id,region" for a
+    /// composite key)
     #[arg(long)]
     array_id_key: Option<String>,
 
+    /// Match documents of multi-document YAML streams by these comma-separated keys
+    /// instead of by position (e.g., "kind,metadata.namespace,metadata.name")
+    #[arg(long)]
+    yaml_doc_key: Option<String>,
+
+    /// Show which YAML anchor a changed value came from (via alias or merge key)
+    #[arg(long)]
+    show_yaml_anchors: bool,
+
     /// Show N lines of context around differences (unified diff style)
     #[arg(long)]
     context: Option<usize>,
@@ -66,6 +197,10 @@ struct Args {
     #[arg(long)]
     ignore_case: bool,
 
+    /// Report objects whose keys appear in a different order
+    #[arg(long)]
+    check_key_order: bool,
+
     /// Suppress normal output; return only exit status (diff -q style)
     #[arg(short, long)]
     quiet: bool,
@@ -94,15 +229,36 @@ enum OutputFormat {
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Serialize, Deserialize)]
 enum Format {
     Json,
+    Json5,
+    Jsonc,
     Yaml,
     Toml,
     Ini,
     Xml,
     Csv,
+    Tsv,
+    Hcl,
+    Properties,
+    Dotenv,
+    Ron,
+    Jsonnet,
+    Protobuf,
+    Msgpack,
+    Cbor,
+    Bson,
+    Plist,
+    Parquet,
+    Arrow,
+    Spreadsheet,
+    Sqlite,
+    FrontMatter,
 }
 
 fn infer_format_from_path(path: &Path) -> Option<Format> {
-    if path.to_str() == Some("-") {
+    if Compression::from_path(path).is_some() {
+        // config.yaml.gz is read as YAML once decompressed
+        infer_format_from_path(Path::new(path.file_stem()?))
+    } else if path.to_str() == Some("-") {
         // Cannot infer format from stdin, user must specify --format
         None
     } else {
@@ -110,16 +266,125 @@ fn infer_format_from_path(path: &Path) -> Option<Format> {
             .and_then(|ext| ext.to_str())
             .and_then(|ext_str| match ext_str.to_lowercase().as_str() {
                 "json" => Some(Format::Json),
+                "json5" => Some(Format::Json5),
+                "jsonc" => Some(Format::Jsonc),
                 "yaml" | "yml" => Some(Format::Yaml),
                 "toml" => Some(Format::Toml),
-                "ini" => Some(Format::Ini),
+                "ini" | "cfg" | "conf" => Some(Format::Ini),
                 "xml" => Some(Format::Xml),
                 "csv" => Some(Format::Csv),
+                "tsv" => Some(Format::Tsv),
+                "tf" | "tfvars" | "hcl" => Some(Format::Hcl),
+                "properties" => Some(Format::Properties),
+                "env" => Some(Format::Dotenv),
+                "ron" => Some(Format::Ron),
+                "jsonnet" | "libsonnet" => Some(Format::Jsonnet),
+                "binpb" | "txtpb" | "textproto" | "pbtxt" => Some(Format::Protobuf),
+                "msgpack" | "mpk" => Some(Format::Msgpack),
+                "cbor" => Some(Format::Cbor),
+                "bson" => Some(Format::Bson),
+                "plist" | "entitlements" => Some(Format::Plist),
+                "parquet" => Some(Format::Parquet),
+                "arrow" | "arrows" | "feather" | "ipc" => Some(Format::Arrow),
+                "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => Some(Format::Spreadsheet),
+                "sqlite" | "sqlite3" | "db" | "db3" => Some(Format::Sqlite),
+                "md" | "markdown" | "mdx" => Some(Format::FrontMatter),
                 _ => None,
             })
+            .or_else(|| infer_format_from_file_name(path))
+    }
+}
+
+/// Protobuf encoding named by the file extension, e.g. `.txtpb` for the text format
+fn protobuf_encoding_from_path(path: &Path) -> ProtobufEncoding {
+    if Compression::from_path(path).is_some() {
+        return path.file_stem().map_or(ProtobufEncoding::Auto, |stem| {
+            protobuf_encoding_from_path(Path::new(stem))
+        });
+    }
+    match path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .as_deref()
+    {
+        Some("binpb") => ProtobufEncoding::Binary,
+        Some("txtpb" | "textproto" | "pbtxt") => ProtobufEncoding::Text,
+        _ => ProtobufEncoding::Auto,
     }
 }
 
+/// Formats recognised by file name rather than extension (e.g. `.env.production`)
+fn infer_format_from_file_name(path: &Path) -> Option<Format> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name == ".env" || name.starts_with(".env.") {
+        Some(Format::Dotenv)
+    } else {
+        None
+    }
+}
+
+/// Recognise the format from the content, for inputs whose name says nothing (stdin,
+/// `/dev/fd/63`): binary formats by their encoding, text formats by their structure.
+/// Fails when the text could be read as several formats.
+fn infer_format_from_content(content: &[u8]) -> Result<Option<Format>> {
+    if let Some(format) = detect_binary_format(content) {
+        return Ok(Some(match format {
+            BinaryFormat::MessagePack => Format::Msgpack,
+            BinaryFormat::Cbor => Format::Cbor,
+            BinaryFormat::Bson => Format::Bson,
+            BinaryFormat::Plist => Format::Plist,
+            BinaryFormat::Parquet => Format::Parquet,
+            BinaryFormat::ArrowIpc => Format::Arrow,
+            BinaryFormat::Sqlite => Format::Sqlite,
+        }));
+    }
+    Ok(sniff_text_format(content)?.map(|format| match format {
+        TextFormat::Json => Format::Json,
+        TextFormat::Yaml => Format::Yaml,
+        TextFormat::Toml => Format::Toml,
+        TextFormat::Xml => Format::Xml,
+        TextFormat::Ini => Format::Ini,
+        TextFormat::Csv => Format::Csv,
+    }))
+}
+
+/// Infer the formats of two inputs from their extensions, then their content, along with
+/// how each was chosen. An input that neither settles follows the other one, but content
+/// that reads as several formats is an error even when the other input's format is known:
+/// following it could silently parse the input the wrong way.
+fn infer_formats(inputs: [(&Path, &[u8]); 2]) -> Result<[(Format, &'static str); 2]> {
+    let [first, second] = inputs.map(|(path, content)| match infer_format_from_path(path) {
+        Some(format) => Ok((Some(format), "file extension")),
+        None => match infer_format_from_content(content) {
+            Ok(format) => Ok((format, "content")),
+            Err(e) => bail!(
+                "Could not infer the format of {}: {e}. Please specify --format.",
+                path.display()
+            ),
+        },
+    });
+    let inferred = [first?, second?];
+    let Some(fallback) = inferred[0].0.or(inferred[1].0) else {
+        bail!(
+            "Could not infer the format of {} or {} from extensions or content. Please specify --format.",
+            inputs[0].0.display(),
+            inputs[1].0.display()
+        );
+    };
+    Ok(inferred.map(|(known, source)| match known {
+        Some(format) => (format, source),
+        None => (fallback, "as the other input"),
+    }))
+}
+
+fn format_name(format: Format) -> String {
+    format.to_possible_value().map_or_else(
+        || format!("{format:?}"),
+        |value| value.get_name().to_string(),
+    )
+}
+
 fn should_auto_optimize(input1: &Path, input2: &Path) -> Result<bool> {
     // Auto-optimize for files larger than 1MB
     let large_file_threshold = 1024 * 1024; // 1MB
@@ -139,31 +404,442 @@ fn should_auto_optimize(input1: &Path, input2: &Path) -> Result<bool> {
     Ok(size1 > large_file_threshold || size2 > large_file_threshold)
 }
 
-fn read_input(file_path: &Path) -> Result<String> {
+/// Read an input, decompressing gzip, zstd, bzip2 and xz data on the fly
+fn read_input(file_path: &Path) -> Result<Vec<u8>> {
     if file_path.to_str() == Some("-") {
-        let mut buffer = String::new();
-        io::stdin()
-            .read_to_string(&mut buffer)
-            .context("Failed to read from stdin")?;
-        Ok(buffer)
+        let (content, _) =
+            read_decompressed(io::stdin().lock(), None).context("Failed to read from stdin")?;
+        Ok(content)
     } else {
-        fs::read_to_string(file_path)
-            .with_context(|| format!("Failed to read file: {}", file_path.display()))
+        let file = fs::File::open(file_path)
+            .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+        let (content, _) =
+            read_decompressed(io::BufReader::new(file), Compression::from_path(file_path))
+                .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+        Ok(content)
     }
 }
 
-fn parse_content(content: &str, format: Format) -> Result<Value> {
+/// The content of an input: read into memory, or left on disk for the Parquet, Arrow
+/// and SQLite readers, which read what they need from the file as they go
+enum Content {
+    Read(Bytes),
+    OnDisk(PathBuf),
+}
+
+impl Content {
+    /// The bytes read, empty for a file left on disk
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Content::Read(content) => content,
+            Content::OnDisk(_) => &[],
+        }
+    }
+}
+
+/// Open an input whose format may already be known: uncompressed Parquet, Arrow and
+/// SQLite files are left on disk, anything else is read with [`read_input`]
+fn open_input(file_path: &Path, format: Option<Format>) -> Result<Content> {
+    let read_from_disk = matches!(
+        format,
+        Some(Format::Parquet | Format::Arrow | Format::Sqlite)
+    ) && file_path.to_str() != Some("-")
+        && Compression::from_path(file_path).is_none()
+        && file_path.is_file();
+    if read_from_disk {
+        Ok(Content::OnDisk(file_path.to_path_buf()))
+    } else {
+        Ok(Content::Read(Bytes::from(read_input(file_path)?)))
+    }
+}
+
+/// Input handling settings that apply to every input, collected from the command line
+#[derive(Debug, Clone, Default)]
+struct ParseOptions {
+    lenient_json: bool,
+    properties: PropertiesOptions,
+    xml: XmlOptions,
+    ini: IniOptions,
+    csv: CsvOptions,
+    /// Delimiter given on the command line, overriding the per-format default
+    csv_delimiter: Option<u8>,
+    /// Identity keys used to pair documents of multi-document YAML streams
+    yaml_doc_keys: Option<Vec<String>>,
+    binary: BinaryOptions,
+    jsonnet: JsonnetOptions,
+    columnar: ColumnarOptions,
+    spreadsheet: SpreadsheetOptions,
+    front_matter: FrontMatterOptions,
+    /// Message schema, when --proto-descriptor and --proto-message are given
+    protobuf: Option<ProtobufOptions>,
+}
+
+impl ParseOptions {
+    fn from_args(args: &Args) -> Result<Self> {
+        let protobuf = match (&args.proto_descriptor, &args.proto_message) {
+            (Some(descriptor), Some(message)) => Some(ProtobufOptions {
+                descriptor_set: fs::read(descriptor).with_context(|| {
+                    format!("Failed to read descriptor set: {}", descriptor.display())
+                })?,
+                message: message.clone(),
+                bytes_as_hex: args.bytes_as_hex,
+                encoding: ProtobufEncoding::Auto,
+            }),
+            (None, None) => None,
+            _ => bail!("--proto-descriptor and --proto-message must be given together"),
+        };
+        // Spreadsheet tables take their header and key settings from the CSV options
+        let csv = CsvOptions {
+            quote: (!args.csv_no_quoting).then_some(args.csv_quote),
+            has_headers: !args.csv_no_headers,
+            key_columns: split_list(args.csv_key.as_deref()).unwrap_or_default(),
+            ..CsvOptions::default()
+        };
+        Ok(Self {
+            lenient_json: args.lenient_json,
+            properties: PropertiesOptions {
+                expand_dotted_keys: args.expand_dotted_keys,
+                coerce_scalars: args.coerce_scalars,
+            },
+            xml: XmlOptions {
+                elements_as_arrays: args.xml_arrays,
+            },
+            ini: IniOptions {
+                case_sensitive: args.ini_case_sensitive,
+                global_section: (!args.ini_root_globals).then(|| "default".to_string()),
+                duplicate_keys_as_arrays: args.ini_duplicates_as_arrays,
+                inline_comment_chars: match &args.ini_inline_comments {
+                    Some(chars) => chars.chars().collect(),
+                    None => IniOptions::default().inline_comment_chars,
+                },
+                allow_no_value: !args.ini_require_values,
+            },
+            csv: csv.clone(),
+            csv_delimiter: args.csv_delimiter,
+            yaml_doc_keys: split_list(args.yaml_doc_key.as_deref()),
+            binary: BinaryOptions {
+                bytes_as_hex: args.bytes_as_hex,
+            },
+            jsonnet: JsonnetOptions {
+                library_paths: args.jsonnet_jpath.clone(),
+                ext_strs: args.ext_str.clone(),
+            },
+            columnar: ColumnarOptions {
+                key_columns: split_list(args.csv_key.as_deref()).unwrap_or_default(),
+                bytes_as_hex: args.bytes_as_hex,
+                ..ColumnarOptions::default()
+            },
+            spreadsheet: SpreadsheetOptions {
+                table: csv,
+                compare_formulas: args.compare_formulas,
+            },
+            front_matter: FrontMatterOptions {
+                include_body: args.front_matter_body,
+            },
+            protobuf,
+        })
+    }
+
+    fn csv_options(&self, default_delimiter: u8) -> CsvOptions {
+        CsvOptions {
+            delimiter: self.csv_delimiter.unwrap_or(default_delimiter),
+            ..self.csv.clone()
+        }
+    }
+}
+
+/// Split a comma-separated option value, dropping empty entries
+fn split_list(list: Option<&str>) -> Option<Vec<String>> {
+    list.map(|list| {
+        list.split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    })
+}
+
+fn parse_ext_str(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=VALUE, got \"{value}\"")),
+    }
+}
+
+fn parse_csv_char(value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!(
+            "expected a single ASCII character or \"tab\", got \"{value}\""
+        )),
+    }
+}
+
+fn parse_content(
+    path: &Path,
+    input: &Content,
+    format: Format,
+    options: &ParseOptions,
+) -> Result<Value> {
+    let content = input.bytes();
+    // Every format but the binary ones is read as UTF-8 text
+    let text = || std::str::from_utf8(content).context("Input is not valid UTF-8 text");
     match format {
-        Format::Json => serde_json::from_str(content).context("Failed to parse JSON"),
-        Format::Yaml => serde_yml::from_str(content).context("Failed to parse YAML"),
-        Format::Toml => toml::from_str(content).context("Failed to parse TOML"),
-        Format::Ini => parse_ini(content).context("Failed to parse INI"),
-        Format::Xml => parse_xml(content).context("Failed to parse XML"),
-        Format::Csv => parse_csv(content).context("Failed to parse CSV"),
+        Format::Json if options.lenient_json => {
+            parse_jsonc(text()?).context("Failed to parse JSON")
+        }
+        Format::Json => parse_json(text()?).context("Failed to parse JSON"),
+        Format::Json5 => parse_json5(text()?).context("Failed to parse JSON5"),
+        Format::Jsonc => parse_jsonc(text()?).context("Failed to parse JSONC"),
+        Format::Yaml => parse_yaml(text()?).context("Failed to parse YAML"),
+        Format::Toml => parse_toml(text()?).context("Failed to parse TOML"),
+        Format::Ini => parse_ini_with_options(text()?, &options.ini).context("Failed to parse INI"),
+        Format::Xml => parse_xml_with_options(text()?, &options.xml).context("Failed to parse XML"),
+        Format::Csv => parse_csv_with_options(text()?, &options.csv_options(b','))
+            .context("Failed to parse CSV"),
+        Format::Tsv => parse_csv_with_options(text()?, &options.csv_options(b'\t'))
+            .context("Failed to parse TSV"),
+        Format::Hcl => parse_hcl(text()?).context("Failed to parse HCL"),
+        Format::Properties => parse_properties_with_options(text()?, &options.properties)
+            .context("Failed to parse properties"),
+        Format::Dotenv => parse_dotenv(text()?).context("Failed to parse dotenv"),
+        Format::Ron => parse_ron(text()?).context("Failed to parse RON"),
+        Format::FrontMatter => parse_front_matter(text()?, &options.front_matter)
+            .context("Failed to parse Markdown front matter"),
+        // Evaluated from the file itself, so that its imports resolve
+        Format::Jsonnet if path.to_str() == Some("-") => {
+            bail!("Jsonnet input cannot be read from stdin; pass the file path")
+        }
+        Format::Jsonnet => evaluate_jsonnet(path, &options.jsonnet),
+        Format::Protobuf => match &options.protobuf {
+            Some(protobuf) => {
+                let options = ProtobufOptions {
+                    encoding: protobuf_encoding_from_path(path),
+                    ..protobuf.clone()
+                };
+                parse_protobuf(content, &options).context("Failed to parse protobuf")
+            }
+            None => bail!("Protobuf input needs --proto-descriptor and --proto-message"),
+        },
+        Format::Msgpack => parse_msgpack_with_options(content, &options.binary)
+            .context("Failed to parse MessagePack"),
+        Format::Cbor => {
+            parse_cbor_with_options(content, &options.binary).context("Failed to parse CBOR")
+        }
+        Format::Bson => {
+            parse_bson_with_options(content, &options.binary).context("Failed to parse BSON")
+        }
+        Format::Plist => {
+            parse_plist_with_options(content, &options.binary).context("Failed to parse plist")
+        }
+        Format::Parquet => match input {
+            Content::OnDisk(path) => parse_parquet(open_file(path)?, &options.columnar),
+            Content::Read(content) => parse_parquet(content.clone(), &options.columnar),
+        }
+        .context("Failed to parse Parquet"),
+        Format::Arrow => match input {
+            Content::OnDisk(path) => parse_arrow_ipc(open_file(path)?, &options.columnar),
+            Content::Read(content) => {
+                parse_arrow_ipc(io::Cursor::new(content.as_ref()), &options.columnar)
+            }
+        }
+        .context("Failed to parse Arrow IPC"),
+        Format::Spreadsheet => {
+            parse_spreadsheet(content, &options.spreadsheet).context("Failed to parse spreadsheet")
+        }
+        // Opened by SQLite itself, which reads the pages it needs from the file
+        Format::Sqlite if path.to_str() == Some("-") => {
+            bail!("SQLite input cannot be read from stdin; pass the database path")
+        }
+        Format::Sqlite => match input {
+            Content::OnDisk(path) => parse_sqlite(path, &options.binary),
+            Content::Read(_) => bail!(
+                "SQLite input must be an uncompressed database file, not {}",
+                path.display()
+            ),
+        },
     }
 }
 
-fn print_cli_output_basic(mut differences: Vec<DiffResult>, _v1: &Value, _v2: &Value) {
+fn open_file(path: &Path) -> Result<fs::File> {
+    fs::File::open(path).with_context(|| format!("Failed to read file: {}", path.display()))
+}
+
+/// Parse an input into its documents: one per `---` section for YAML, otherwise one
+fn parse_documents(
+    path: &Path,
+    content: &Content,
+    format: Format,
+    options: &ParseOptions,
+) -> Result<Vec<Value>> {
+    match format {
+        Format::Yaml => {
+            let text =
+                std::str::from_utf8(content.bytes()).context("Input is not valid UTF-8 text")?;
+            parse_yaml_documents(text).context("Failed to parse YAML")
+        }
+        _ => Ok(vec![parse_content(path, content, format, options)?]),
+    }
+}
+
+/// Open a Parquet or Arrow input for reading its rows batch by batch
+fn read_columnar<'a>(
+    input: &'a Content,
+    format: Format,
+    options: &ParseOptions,
+) -> Result<ColumnarRows<'a>> {
+    match (format, input) {
+        (Format::Parquet, Content::OnDisk(path)) => {
+            read_parquet(open_file(path)?, &options.columnar)
+        }
+        (Format::Parquet, Content::Read(content)) => {
+            read_parquet(content.clone(), &options.columnar)
+        }
+        (Format::Arrow, Content::OnDisk(path)) => {
+            read_arrow_ipc(open_file(path)?, &options.columnar)
+        }
+        (Format::Arrow, Content::Read(content)) => {
+            read_arrow_ipc(io::Cursor::new(content.as_ref()), &options.columnar)
+        }
+        _ => bail!("{} is not a columnar format", format_name(format)),
+    }
+}
+
+/// Diff parsed inputs, pairing documents when either side is a multi-document stream
+fn diff_parsed(
+    docs1: &[Value],
+    docs2: &[Value],
+    options: &ParseOptions,
+    config: &DiffConfig,
+) -> Vec<DiffResult> {
+    if uses_document_labels(docs1, docs2, options) {
+        diff_documents(docs1, docs2, options.yaml_doc_keys.as_deref(), config)
+    } else {
+        diff_with_config(&docs1[0], &docs2[0], config)
+    }
+}
+
+/// Give child elements repeated in one XML input the same shape in the other
+fn align_xml_inputs(
+    (format1, docs1): (Format, &mut [Value]),
+    (format2, docs2): (Format, &mut [Value]),
+) {
+    if format1 == Format::Xml && format2 == Format::Xml {
+        align_xml_repeated_elements(&mut docs1[0], &mut docs2[0]);
+    }
+}
+
+/// Rewrite difference paths in the notation of the inputs: XPath-like for XML
+fn format_paths(
+    differences: Vec<DiffResult>,
+    (format1, docs1): (Format, &[Value]),
+    (format2, docs2): (Format, &[Value]),
+) -> Vec<DiffResult> {
+    if format1 == Format::Xml && format2 == Format::Xml {
+        xml_diff_paths(differences, &docs1[0], &docs2[0])
+    } else {
+        differences
+    }
+}
+
+/// Keys that the rows of SQLite tables are matched by. A table in both inputs keeps
+/// the key of the old one, so rows are still paired when its primary key changes.
+fn table_row_keys(
+    (format1, docs1): (Format, &[Value]),
+    (format2, docs2): (Format, &[Value]),
+) -> BTreeMap<String, Vec<String>> {
+    let mut keys = BTreeMap::new();
+    for (format, docs) in [(format2, docs2), (format1, docs1)] {
+        if format == Format::Sqlite {
+            keys.extend(sqlite_row_keys(&docs[0]));
+        }
+    }
+    keys
+}
+
+/// Whether difference paths are prefixed with document labels (`[0].spec`)
+fn uses_document_labels(docs1: &[Value], docs2: &[Value], options: &ParseOptions) -> bool {
+    options.yaml_doc_keys.is_some() || docs1.len() != 1 || docs2.len() != 1
+}
+
+/// Anchors that the values of both inputs came from, keyed by difference path
+#[derive(Debug, Default)]
+struct AnchorSources {
+    old: BTreeMap<String, String>,
+    new: BTreeMap<String, String>,
+}
+
+impl AnchorSources {
+    /// Annotation for a difference, e.g. `(from &defaults)`, or `(was from &defaults)`
+    /// when only the old value came from an anchor
+    fn annotation(&self, diff: &DiffResult) -> Option<String> {
+        let from = |anchor: &str| format!("(from &{anchor})");
+        match diff {
+            DiffResult::Removed(k, _) => anchor_for_path(&self.old, k).map(from),
+            DiffResult::Added(k, _) => anchor_for_path(&self.new, k).map(from),
+            DiffResult::Modified(k, _, _)
+            | DiffResult::TypeChanged(k, _, _)
+            | DiffResult::KeyOrderChanged(k, _, _) => {
+                anchor_for_path(&self.new, k).map(from).or_else(|| {
+                    anchor_for_path(&self.old, k).map(|anchor| format!("(was from &{anchor})"))
+                })
+            }
+        }
+    }
+}
+
+/// Map the anchor provenance of a YAML input onto difference paths
+fn yaml_anchor_paths(
+    content: &str,
+    docs: &[Value],
+    options: &ParseOptions,
+    labelled: bool,
+) -> Result<BTreeMap<String, String>> {
+    let sources = yaml_anchor_sources(content).context("Failed to parse YAML")?;
+    if !labelled {
+        return Ok(sources.into_iter().next().unwrap_or_default());
+    }
+
+    let mut paths = BTreeMap::new();
+    for (index, (doc, doc_sources)) in docs.iter().zip(sources).enumerate() {
+        let label = document_label(doc, index, options.yaml_doc_keys.as_deref());
+        for (path, anchor) in doc_sources {
+            paths.insert(document_path(&label, &path), anchor);
+        }
+    }
+    Ok(paths)
+}
+
+fn documents_into_value(mut documents: Vec<Value>) -> Value {
+    if documents.len() == 1 {
+        documents.remove(0)
+    } else {
+        Value::Array(documents)
+    }
+}
+
+/// Nesting depth of a difference path, for both dotted and XPath-like (`/a/b[2]`) paths
+fn path_depth(path: &str) -> usize {
+    match path.strip_prefix('/') {
+        Some(xpath) => xpath.chars().filter(|&c| c == '/' || c == '[').count(),
+        None => {
+            // Dots inside quoted keys and values (`data["config.json"]`) do not nest
+            let (mut depth, mut quoted, mut escaped) = (0, false, false);
+            for c in path.chars() {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' if quoted => escaped = true,
+                    '"' => quoted = !quoted,
+                    '.' | '[' if !quoted => depth += 1,
+                    _ => {}
+                }
+            }
+            depth
+        }
+    }
+}
+
+fn print_cli_output_basic(differences: Vec<DiffResult>, _v1: &Value, _v2: &Value) {
     if differences.is_empty() {
         // Follow diff convention: output nothing when no differences
         return;
@@ -175,34 +851,47 @@ fn print_cli_output_basic(mut differences: Vec<DiffResult>, _v1: &Value, _v2: &V
             DiffResult::Removed(k, _) => k.clone(),
             DiffResult::Modified(k, _, _) => k.clone(),
             DiffResult::TypeChanged(k, _, _) => k.clone(),
+            DiffResult::KeyOrderChanged(k, _, _) => k.clone(),
         }
     };
 
-    differences.sort_by_key(get_key);
-
+    // Differences are already in document order
     for diff in &differences {
         let key = get_key(diff);
         // Indent based on the depth of the key
-        let depth = key.chars().filter(|&c| c == '.' || c == '[').count();
+        let depth = path_depth(&key);
         let indent = "  ".repeat(depth);
 
         let diff_str = match diff {
-            DiffResult::Added(k, value) => format!("+ {k}: {value}").blue(),
-            DiffResult::Removed(k, value) => format!("- {k}: {value}").yellow(),
-            DiffResult::Modified(k, v1, v2) => format!("~ {k}: {v1} -> {v2}").cyan(),
+            DiffResult::Added(k, value) => format!("+ {k}: {}", format_value(value)).blue(),
+            DiffResult::Removed(k, value) => format!("- {k}: {}", format_value(value)).yellow(),
+            DiffResult::Modified(k, v1, v2) => {
+                format!("~ {k}: {} -> {}", format_value(v1), format_value(v2)).cyan()
+            }
             DiffResult::TypeChanged(k, v1, v2) => format!(
-                "! {k}: {v1} ({}) -> {v2} ({})",
+                "! {k}: {} ({}) -> {} ({})",
+                format_value(v1),
                 value_type_name(v1),
+                format_value(v2),
                 value_type_name(v2)
             )
             .magenta(),
+            DiffResult::KeyOrderChanged(k, v1, v2) => {
+                format!("^ {k}: {} -> {}", format_value(v1), format_value(v2)).green()
+            }
         };
 
         println!("{indent}{diff_str}");
     }
 }
 
-fn print_cli_output(mut differences: Vec<DiffResult>, _v1: &Value, _v2: &Value, _args: &Args) {
+fn print_cli_output(
+    differences: Vec<DiffResult>,
+    _v1: &Value,
+    _v2: &Value,
+    _args: &Args,
+    anchors: &AnchorSources,
+) {
     if differences.is_empty() {
         // Follow diff convention: output nothing when no differences
         return;
@@ -214,30 +903,40 @@ fn print_cli_output(mut differences: Vec<DiffResult>, _v1: &Value, _v2: &Value,
             DiffResult::Removed(k, _) => k.clone(),
             DiffResult::Modified(k, _, _) => k.clone(),
             DiffResult::TypeChanged(k, _, _) => k.clone(),
+            DiffResult::KeyOrderChanged(k, _, _) => k.clone(),
         }
     };
 
-    differences.sort_by_key(get_key);
-
+    // Differences are already in document order
     for diff in &differences {
         let key = get_key(diff);
         // Indent based on the depth of the key
-        let depth = key.chars().filter(|&c| c == '.' || c == '[').count();
+        let depth = path_depth(&key);
         let indent = "  ".repeat(depth);
 
         let diff_str = match diff {
-            DiffResult::Added(k, value) => format!("+ {k}: {value}").blue(),
-            DiffResult::Removed(k, value) => format!("- {k}: {value}").yellow(),
-            DiffResult::Modified(k, v1, v2) => format!("~ {k}: {v1} -> {v2}").cyan(),
+            DiffResult::Added(k, value) => format!("+ {k}: {}", format_value(value)).blue(),
+            DiffResult::Removed(k, value) => format!("- {k}: {}", format_value(value)).yellow(),
+            DiffResult::Modified(k, v1, v2) => {
+                format!("~ {k}: {} -> {}", format_value(v1), format_value(v2)).cyan()
+            }
             DiffResult::TypeChanged(k, v1, v2) => format!(
-                "! {k}: {v1} ({}) -> {v2} ({})",
+                "! {k}: {} ({}) -> {} ({})",
+                format_value(v1),
                 value_type_name(v1),
+                format_value(v2),
                 value_type_name(v2)
             )
             .magenta(),
+            DiffResult::KeyOrderChanged(k, v1, v2) => {
+                format!("^ {k}: {} -> {}", format_value(v1), format_value(v2)).green()
+            }
         };
 
-        println!("{indent}{diff_str}");
+        match anchors.annotation(diff) {
+            Some(annotation) => println!("{indent}{diff_str} {}", annotation.dimmed()),
+            None => println!("{indent}{diff_str}"),
+        }
     }
 }
 
@@ -246,6 +945,21 @@ fn print_json_output(differences: Vec<DiffResult>) -> Result<()> {
         // Follow diff convention: output nothing when no differences
         return Ok(());
     }
+    // Extension values (date/times, NaN, tags) in their native syntax
+    let differences: Vec<DiffResult> = differences
+        .into_iter()
+        .map(|diff| match diff {
+            DiffResult::Added(key, value) => DiffResult::Added(key, to_plain_json(&value)),
+            DiffResult::Removed(key, value) => DiffResult::Removed(key, to_plain_json(&value)),
+            DiffResult::Modified(key, old_value, new_value) => {
+                DiffResult::Modified(key, to_plain_json(&old_value), to_plain_json(&new_value))
+            }
+            DiffResult::TypeChanged(key, old_value, new_value) => {
+                DiffResult::TypeChanged(key, to_plain_json(&old_value), to_plain_json(&new_value))
+            }
+            other @ DiffResult::KeyOrderChanged(..) => other,
+        })
+        .collect();
     println!("{}", serde_json::to_string_pretty(&differences)?);
     Ok(())
 }
@@ -271,10 +985,16 @@ fn print_yaml_output(differences: Vec<DiffResult>) -> Result<()> {
             DiffResult::TypeChanged(key, old_value, new_value) => serde_json::json!({
                 "TypeChanged": [key, old_value, new_value]
             }),
+            DiffResult::KeyOrderChanged(key, old_order, new_order) => serde_json::json!({
+                "KeyOrderChanged": [key, old_order, new_order]
+            }),
         })
         .collect();
 
-    println!("{}", serde_yml::to_string(&yaml_data)?);
+    println!(
+        "{}",
+        serde_yml::to_string(&to_yaml_value(&Value::Array(yaml_data)))?
+    );
     Ok(())
 }
 
@@ -289,7 +1009,7 @@ fn extract_path_value(value: &Value, path: &str) -> Option<Value> {
             let index_str = &part[bracket_pos + 1..part.len() - 1];
 
             // First get the object field
-            current = current.get(key)?;
+            current = current.get(escape_reserved_key(key.to_string()))?;
 
             // Then get the array element
             if let Ok(index) = index_str.parse::<usize>() {
@@ -299,7 +1019,7 @@ fn extract_path_value(value: &Value, path: &str) -> Option<Value> {
                 return None; // For now, simplified implementation
             }
         } else {
-            current = current.get(part)?;
+            current = current.get(escape_reserved_key(part.to_string()))?;
         }
     }
 
@@ -307,8 +1027,8 @@ fn extract_path_value(value: &Value, path: &str) -> Option<Value> {
 }
 
 fn print_unified_output_basic(v1: &Value, v2: &Value) -> Result<()> {
-    let content1_pretty = serde_json::to_string_pretty(v1)?;
-    let content2_pretty = serde_json::to_string_pretty(v2)?;
+    let content1_pretty = serde_json::to_string_pretty(&to_plain_json(v1))?;
+    let content2_pretty = serde_json::to_string_pretty(&to_plain_json(v2))?;
 
     let diff = similar::TextDiff::from_lines(&content1_pretty, &content2_pretty);
 
@@ -324,8 +1044,8 @@ fn print_unified_output_basic(v1: &Value, v2: &Value) -> Result<()> {
 }
 
 fn print_unified_output(v1: &Value, v2: &Value, args: &Args) -> Result<()> {
-    let content1_pretty = serde_json::to_string_pretty(v1)?;
-    let content2_pretty = serde_json::to_string_pretty(v2)?;
+    let content1_pretty = serde_json::to_string_pretty(&to_plain_json(v1))?;
+    let content2_pretty = serde_json::to_string_pretty(&to_plain_json(v2))?;
 
     let diff = similar::TextDiff::from_lines(&content1_pretty, &content2_pretty);
 
@@ -392,6 +1112,29 @@ fn run() -> Result<()> {
         None
     };
 
+    let decode_embedded = if let Some(regex_str) = &args.decode_embedded {
+        let regex = Regex::new(regex_str).context("Invalid regex for --decode-embedded")?;
+        if args.verbose {
+            eprintln!("Embedded string decoding:");
+            eprintln!("  Path pattern: {regex_str}");
+        }
+        Some(regex)
+    } else {
+        None
+    };
+
+    let decode_base64 = if let Some(regex_str) = &args.decode_base64 {
+        let regex = Regex::new(regex_str).context("Invalid regex for --decode-base64")?;
+        if args.verbose {
+            eprintln!("Base64 decoding:");
+            eprintln!("  Path pattern: {regex_str}");
+            eprintln!("  Show secrets: {}", args.show_secrets);
+        }
+        Some(regex)
+    } else {
+        None
+    };
+
     let epsilon = args.epsilon;
     if let Some(eps) = epsilon {
         if args.verbose {
@@ -400,7 +1143,13 @@ fn run() -> Result<()> {
         }
     }
 
-    let array_id_key = args.array_id_key.as_deref();
+    // Table rows keyed with --csv-key are matched like any other array
+    let array_id_key = match (args.array_id_key.as_deref(), args.csv_key.as_deref()) {
+        (Some(id_key), Some(csv_key)) if split_list(Some(id_key)) != split_list(Some(csv_key)) => {
+            bail!("--array-id-key and --csv-key name different keys: {id_key} and {csv_key}")
+        }
+        (id_key, csv_key) => id_key.or(csv_key),
+    };
     if let Some(id_key) = array_id_key {
         if args.verbose {
             eprintln!("Array tracking configuration:");
@@ -419,17 +1168,24 @@ fn run() -> Result<()> {
     }
 
     // Handle directory comparison (Unix diff compatible)
-    if args.input1.is_dir() || args.input2.is_dir() {
-        if !args.input1.is_dir() || !args.input2.is_dir() {
+    // Archives are compared like the directories they were packed from
+    let is_tree = |path: &Path| path.is_dir() || archive_kind(path).is_some();
+    if is_tree(&args.input1) || is_tree(&args.input2) {
+        if !is_tree(&args.input1) || !is_tree(&args.input2) {
             bail!("Cannot compare directory and file. Both inputs must be directories or both must be files.");
         }
         let has_differences = compare_directories(
             &args.input1,
             &args.input2,
             args.format,
+            &ParseOptions::from_args(&args)?,
             output_format,
             args.path,
             ignore_keys_regex.as_ref(),
+            decode_embedded.as_ref(),
+            decode_base64.as_ref(),
+            args.show_secrets,
+            args.check_key_order,
             epsilon,
             array_id_key,
             use_memory_optimization,
@@ -449,26 +1205,32 @@ fn run() -> Result<()> {
     // Handle single file/stdin comparison
     let start_time = Instant::now();
 
-    let content1 = read_input(&args.input1)?;
-    let content2 = read_input(&args.input2)?;
+    let content1 = open_input(
+        &args.input1,
+        args.format.or_else(|| infer_format_from_path(&args.input1)),
+    )?;
+    let content2 = open_input(
+        &args.input2,
+        args.format.or_else(|| infer_format_from_path(&args.input2)),
+    )?;
 
     // Verbose file size information
     if args.verbose {
         let size1 = if args.input1.to_str() == Some("-") {
-            content1.len()
+            content1.bytes().len()
         } else {
             args.input1
                 .metadata()
                 .map(|m| m.len() as usize)
-                .unwrap_or(content1.len())
+                .unwrap_or(content1.bytes().len())
         };
         let size2 = if args.input2.to_str() == Some("-") {
-            content2.len()
+            content2.bytes().len()
         } else {
             args.input2
                 .metadata()
                 .map(|m| m.len() as usize)
-                .unwrap_or(content2.len())
+                .unwrap_or(content2.bytes().len())
         };
 
         eprintln!("Input file information:");
@@ -476,43 +1238,98 @@ fn run() -> Result<()> {
         eprintln!("  Input 2 size: {size2} bytes");
     }
 
-    let input_format = if let Some(fmt) = args.format {
-        fmt
+    // Each input keeps its own format so that e.g. YAML can be compared with .properties;
+    // an input without a usable extension (stdin) follows the other one
+    let (format1, format2) = if let Some(fmt) = args.format {
+        if args.verbose {
+            eprintln!("Input formats:");
+            eprintln!("  Input 1: {} (--format)", format_name(fmt));
+            eprintln!("  Input 2: {} (--format)", format_name(fmt));
+        }
+        (fmt, fmt)
     } else {
-        infer_format_from_path(&args.input1)
-            .or_else(|| infer_format_from_path(&args.input2))
-            .context("Could not infer format from file extensions. Please specify --format.")?
+        let [(format1, source1), (format2, source2)] = infer_formats([
+            (&args.input1, content1.bytes()),
+            (&args.input2, content2.bytes()),
+        ])?;
+        if args.verbose {
+            eprintln!("Input formats:");
+            for (i, format, source) in [(1, format1, source1), (2, format2, source2)] {
+                eprintln!("  Input {i}: {} ({source})", format_name(format));
+            }
+        }
+        (format1, format2)
     };
 
-    let parse_start = Instant::now();
-    let v1: Value = parse_content(&content1, input_format)?;
-    let v2: Value = parse_content(&content2, input_format)?;
-    let parse_time = parse_start.elapsed();
+    let parse_options = ParseOptions::from_args(&args)?;
+    let diff_config = |array_id_keys| DiffConfig {
+        ignore_keys_regex: ignore_keys_regex.clone(),
+        epsilon,
+        array_id_key: array_id_key.map(|s| s.to_string()),
+        array_id_keys,
+        use_memory_optimization,
+        batch_size,
+        ignore_whitespace: args.ignore_whitespace,
+        ignore_case: args.ignore_case,
+        check_key_order: args.check_key_order,
+        decode_embedded: decode_embedded.clone(),
+        decode_base64: decode_base64.clone(),
+        show_secrets: args.show_secrets,
+    };
+    let columnar = |format| matches!(format, Format::Parquet | Format::Arrow);
+
+    let mut anchors = AnchorSources::default();
+    // Parquet and Arrow rows are compared as they are decoded rather than loaded;
+    // unified output renders both whole inputs, so it still parses them
+    let (differences, v1, v2) =
+        if columnar(format1) && columnar(format2) && output_format != OutputFormat::Unified {
+            let diff_start = Instant::now();
+            let rows1 = read_columnar(&content1, format1, &parse_options)?;
+            let rows2 = read_columnar(&content2, format2, &parse_options)?;
+            let differences = diff_columnar(rows1, rows2, &diff_config(BTreeMap::new()))?;
+            if args.verbose {
+                eprintln!("Diff computation time: {:?}", diff_start.elapsed());
+                eprintln!("Total differences found: {}", differences.len());
+            }
+            (differences, Value::Null, Value::Null)
+        } else {
+            let parse_start = Instant::now();
+            let mut docs1 = parse_documents(&args.input1, &content1, format1, &parse_options)?;
+            let mut docs2 = parse_documents(&args.input2, &content2, format2, &parse_options)?;
+            align_xml_inputs((format1, &mut docs1), (format2, &mut docs2));
+            let parse_time = parse_start.elapsed();
+
+            if args.verbose {
+                eprintln!("Parse time: {parse_time:?}");
+            }
 
-    if args.verbose {
-        eprintln!("Parse time: {parse_time:?}");
-    }
+            let diff_start = Instant::now();
+            // Always use configuration-based diff to support all options
+            let config = diff_config(table_row_keys((format1, &docs1), (format2, &docs2)));
+            let differences = diff_parsed(&docs1, &docs2, &parse_options, &config);
+            let differences = format_paths(differences, (format1, &docs1), (format2, &docs2));
+
+            if args.show_yaml_anchors {
+                let labelled = uses_document_labels(&docs1, &docs2, &parse_options);
+                if format1 == Format::Yaml {
+                    let content1 = String::from_utf8_lossy(content1.bytes());
+                    anchors.old = yaml_anchor_paths(&content1, &docs1, &parse_options, labelled)?;
+                }
+                if format2 == Format::Yaml {
+                    let content2 = String::from_utf8_lossy(content2.bytes());
+                    anchors.new = yaml_anchor_paths(&content2, &docs2, &parse_options, labelled)?;
+                }
+            }
+            let v1 = documents_into_value(docs1);
+            let v2 = documents_into_value(docs2);
+            let diff_time = diff_start.elapsed();
 
-    let diff_start = Instant::now();
-    let differences = {
-        // Always use configuration-based diff to support all options
-        let config = DiffConfig {
-            ignore_keys_regex: ignore_keys_regex.clone(),
-            epsilon,
-            array_id_key: array_id_key.map(|s| s.to_string()),
-            use_memory_optimization,
-            batch_size,
-            ignore_whitespace: args.ignore_whitespace,
-            ignore_case: args.ignore_case,
+            if args.verbose {
+                eprintln!("Diff computation time: {diff_time:?}");
+                eprintln!("Total differences found: {}", differences.len());
+            }
+            (differences, v1, v2)
         };
-        diff_with_config(&v1, &v2, &config)
-    };
-    let diff_time = diff_start.elapsed();
-
-    if args.verbose {
-        eprintln!("Diff computation time: {diff_time:?}");
-        eprintln!("Total differences found: {}", differences.len());
-    }
 
     let mut differences = differences;
 
@@ -526,6 +1343,7 @@ fn run() -> Result<()> {
                 DiffResult::Removed(k, _) => k,
                 DiffResult::Modified(k, _, _) => k,
                 DiffResult::TypeChanged(k, _, _) => k,
+                DiffResult::KeyOrderChanged(k, _, _) => k,
             };
             key.starts_with(path)
         });
@@ -556,7 +1374,7 @@ fn run() -> Result<()> {
     } else {
         // Normal output
         match output_format {
-            OutputFormat::Cli => print_cli_output(differences, &v1, &v2, &args),
+            OutputFormat::Cli => print_cli_output(differences, &v1, &v2, &args, &anchors),
             OutputFormat::Json => print_json_output(differences)?,
             OutputFormat::Yaml => print_yaml_output(differences)?,
             OutputFormat::Unified => {
@@ -598,14 +1416,165 @@ fn run() -> Result<()> {
     }
 }
 
+/// A file of a directory or archive being compared
+enum TreeFile {
+    Disk(PathBuf),
+    /// An archive member, read into memory under its path inside the archive
+    Member(PathBuf, Vec<u8>),
+}
+
+impl TreeFile {
+    fn path(&self) -> &Path {
+        match self {
+            TreeFile::Disk(path) | TreeFile::Member(path, _) => path,
+        }
+    }
+
+    fn read(&self, format: Option<Format>) -> Result<Content> {
+        match self {
+            TreeFile::Disk(path) => open_input(path, format),
+            TreeFile::Member(path, content) => {
+                let (content, _) =
+                    read_decompressed(content.as_slice(), Compression::from_path(path))
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                Ok(Content::Read(Bytes::from(content)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Tar,
+    Zip,
+}
+
+/// Recognise `.zip` and `.tar` files, including compressed tarballs (`.tar.gz`, `.tgz`)
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    if !path.is_file() {
+        return None;
+    }
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    let tarball = [".tar", ".tgz", ".tbz2", ".txz"];
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if tarball.iter().any(|ext| name.ends_with(ext))
+        || Compression::from_path(path).is_some()
+            && Path::new(&name).file_stem()?.to_str()?.ends_with(".tar")
+    {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+type TreeFiles = HashMap<PathBuf, TreeFile>;
+type TreeSubdirs = std::collections::HashSet<PathBuf>;
+
+/// List the files of a directory or archive by relative path, and without
+/// `recursive` the subdirectories directly below it
+fn scan_tree(root: &Path, recursive: bool) -> Result<(TreeFiles, TreeSubdirs)> {
+    let mut files = HashMap::new();
+    let mut subdirs = std::collections::HashSet::new();
+
+    let Some(kind) = archive_kind(root) else {
+        let walker = if recursive {
+            WalkDir::new(root)
+        } else {
+            WalkDir::new(root).max_depth(1)
+        };
+
+        for entry in walker.into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path == root {
+                continue; // Skip the root directory itself
+            }
+
+            if path.is_file() {
+                let relative_path = path.strip_prefix(root)?.to_path_buf();
+                files.insert(relative_path, TreeFile::Disk(path.to_path_buf()));
+            } else if path.is_dir() && !recursive {
+                let relative_path = path.strip_prefix(root)?.to_path_buf();
+                subdirs.insert(relative_path);
+            }
+        }
+        return Ok((files, subdirs));
+    };
+
+    // Members are read without extracting them; like a directory walk without
+    // `recursive`, only the top level is listed
+    let mut add_member = |name: &Path, is_dir: bool, content: Option<Vec<u8>>| {
+        let relative_path: PathBuf = name
+            .components()
+            .filter(|c| matches!(c, std::path::Component::Normal(_)))
+            .collect();
+        let depth = relative_path.components().count();
+        if depth == 0 {
+            return;
+        }
+        if !recursive && (depth > 1 || is_dir) {
+            if let Some(top) = relative_path.components().next() {
+                subdirs.insert(PathBuf::from(top.as_os_str()));
+            }
+        } else if let Some(content) = content {
+            files.insert(
+                relative_path.clone(),
+                TreeFile::Member(relative_path, content),
+            );
+        }
+    };
+    let open_error = || format!("Failed to read archive: {}", root.display());
+    match kind {
+        ArchiveKind::Tar => {
+            let tarball = read_input(root)?;
+            let mut archive = tar::Archive::new(tarball.as_slice());
+            for entry in archive.entries().with_context(open_error)? {
+                let mut entry = entry.with_context(open_error)?;
+                let name = entry.path().with_context(open_error)?.into_owned();
+                if entry.header().entry_type().is_dir() {
+                    add_member(&name, true, None);
+                } else if entry.header().entry_type().is_file() {
+                    let mut content = Vec::new();
+                    io::Read::read_to_end(&mut entry, &mut content).with_context(open_error)?;
+                    add_member(&name, false, Some(content));
+                }
+            }
+        }
+        ArchiveKind::Zip => {
+            let file = fs::File::open(root).with_context(open_error)?;
+            let mut archive = zip::ZipArchive::new(file).with_context(open_error)?;
+            for i in 0..archive.len() {
+                let mut member = archive.by_index(i).with_context(open_error)?;
+                // Names escaping the archive root (`../`) are skipped
+                let Some(name) = member.enclosed_name() else {
+                    continue;
+                };
+                if member.is_dir() {
+                    add_member(&name, true, None);
+                } else {
+                    let mut content = Vec::new();
+                    io::Read::read_to_end(&mut member, &mut content).with_context(open_error)?;
+                    add_member(&name, false, Some(content));
+                }
+            }
+        }
+    }
+    Ok((files, subdirs))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn compare_directories(
     dir1: &Path,
     dir2: &Path,
     format_option: Option<Format>,
+    parse_options: &ParseOptions,
     output: OutputFormat,
     filter_path: Option<String>,
     ignore_keys_regex: Option<&Regex>,
+    decode_embedded: Option<&Regex>,
+    decode_base64: Option<&Regex>,
+    show_secrets: bool,
+    check_key_order: bool,
     epsilon: Option<f64>,
     array_id_key: Option<&str>,
     use_memory_optimization: bool,
@@ -613,53 +1582,8 @@ fn compare_directories(
     recursive: bool,
     verbose: bool,
 ) -> Result<bool> {
-    let mut files1: HashMap<PathBuf, PathBuf> = HashMap::new();
-    let mut subdirs1: std::collections::HashSet<PathBuf> = std::collections::HashSet::new();
-
-    let walker1 = if recursive {
-        WalkDir::new(dir1)
-    } else {
-        WalkDir::new(dir1).max_depth(1)
-    };
-
-    for entry in walker1.into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if path == dir1 {
-            continue; // Skip the root directory itself
-        }
-
-        if path.is_file() {
-            let relative_path = path.strip_prefix(dir1)?.to_path_buf();
-            files1.insert(relative_path, path.to_path_buf());
-        } else if path.is_dir() && !recursive {
-            let relative_path = path.strip_prefix(dir1)?.to_path_buf();
-            subdirs1.insert(relative_path);
-        }
-    }
-
-    let mut files2: HashMap<PathBuf, PathBuf> = HashMap::new();
-    let mut subdirs2: std::collections::HashSet<PathBuf> = std::collections::HashSet::new();
-
-    let walker2 = if recursive {
-        WalkDir::new(dir2)
-    } else {
-        WalkDir::new(dir2).max_depth(1)
-    };
-
-    for entry in walker2.into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if path == dir2 {
-            continue; // Skip the root directory itself
-        }
-
-        if path.is_file() {
-            let relative_path = path.strip_prefix(dir2)?.to_path_buf();
-            files2.insert(relative_path, path.to_path_buf());
-        } else if path.is_dir() && !recursive {
-            let relative_path = path.strip_prefix(dir2)?.to_path_buf();
-            subdirs2.insert(relative_path);
-        }
-    }
+    let (files1, subdirs1) = scan_tree(dir1, recursive)?;
+    let (files2, subdirs2) = scan_tree(dir2, recursive)?;
 
     let mut all_relative_paths: std::collections::HashSet<PathBuf> =
         files1.keys().cloned().collect();
@@ -710,39 +1634,43 @@ fn compare_directories(
 --- Comparing {} ---",
                     relative_path.display()
                 );
-                let content1 = read_input(path1)?;
-                let content2 = read_input(path2)?;
-
-                let input_format = if let Some(fmt) = format_option {
-                    fmt
-                } else {
-                    infer_format_from_path(path1)
-                        .or_else(|| infer_format_from_path(path2))
-                        .context(format!(
-                            "Could not infer format for {}. Please specify --format.",
-                            relative_path.display()
-                        ))?
+                let known = |path: &Path| format_option.or_else(|| infer_format_from_path(path));
+                let content1 = path1.read(known(path1.path()))?;
+                let content2 = path2.read(known(path2.path()))?;
+                let (path1, path2) = (path1.path(), path2.path());
+
+                // Like single inputs, each file keeps its own format
+                let (format1, format2) = match format_option {
+                    Some(fmt) => (fmt, fmt),
+                    None => {
+                        let [(format1, _), (format2, _)] =
+                            infer_formats([(path1, content1.bytes()), (path2, content2.bytes())])?;
+                        (format1, format2)
+                    }
                 };
 
-                let v1: Value = parse_content(&content1, input_format)?;
-                let v2: Value = parse_content(&content2, input_format)?;
-
-                let differences = if use_memory_optimization {
-                    // Use optimized diff configuration
-                    let config = DiffConfig {
-                        ignore_keys_regex: ignore_keys_regex.cloned(),
-                        epsilon,
-                        array_id_key: array_id_key.map(|s| s.to_string()),
-                        use_memory_optimization: true,
-                        batch_size,
-                        ignore_whitespace: false, // Directory comparison uses basic options
-                        ignore_case: false,
-                    };
-                    diff_with_config(&v1, &v2, &config)
-                } else {
-                    // Use standard diff for compatibility
-                    diff(&v1, &v2, ignore_keys_regex, epsilon, array_id_key)
+                let mut docs1 = parse_documents(path1, &content1, format1, parse_options)?;
+                let mut docs2 = parse_documents(path2, &content2, format2, parse_options)?;
+                align_xml_inputs((format1, &mut docs1), (format2, &mut docs2));
+
+                let config = DiffConfig {
+                    ignore_keys_regex: ignore_keys_regex.cloned(),
+                    epsilon,
+                    array_id_key: array_id_key.map(|s| s.to_string()),
+                    array_id_keys: table_row_keys((format1, &docs1), (format2, &docs2)),
+                    use_memory_optimization,
+                    batch_size,
+                    ignore_whitespace: false, // Directory comparison uses basic options
+                    ignore_case: false,
+                    check_key_order,
+                    decode_embedded: decode_embedded.cloned(),
+                    decode_base64: decode_base64.cloned(),
+                    show_secrets,
                 };
+                let differences = diff_parsed(&docs1, &docs2, parse_options, &config);
+                let differences = format_paths(differences, (format1, &docs1), (format2, &docs2));
+                let v1 = documents_into_value(docs1);
+                let v2 = documents_into_value(docs2);
 
                 let mut differences = differences;
 
@@ -753,6 +1681,7 @@ fn compare_directories(
                             DiffResult::Removed(k, _) => k,
                             DiffResult::Modified(k, _, _) => k,
                             DiffResult::TypeChanged(k, _, _) => k,
+                            DiffResult::KeyOrderChanged(k, _, _) => k,
                         };
                         key.starts_with(filter_path_str)
                     });
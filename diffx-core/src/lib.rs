@@ -1,13 +1,12 @@
 use regex::Regex;
 use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 // use ini::Ini;
 use anyhow::{anyhow, Result};
 use csv::ReaderBuilder;
-use quick_xml::de::from_str;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufReader, Read, Seek};
 use std::path::Path;
 // Removed ProgressReporter - Unix tools should be pipe-friendly
 
@@ -17,15 +16,18 @@ pub enum DiffResult {
     Removed(String, Value),
     Modified(String, Value, Value),
     TypeChanged(String, Value, Value),
+    /// Same keys in a different order (old key order, new key order)
+    KeyOrderChanged(String, Value, Value),
 }
 
 /// Lightweight diff result that doesn't clone values unnecessarily
 #[derive(Debug, PartialEq, Serialize)]
 pub enum LightweightDiffResult {
-    Added(String, String),               // path, serialized value
-    Removed(String, String),             // path, serialized value
-    Modified(String, String, String),    // path, old_value, new_value
-    TypeChanged(String, String, String), // path, old_value, new_value
+    Added(String, String),                   // path, serialized value
+    Removed(String, String),                 // path, serialized value
+    Modified(String, String, String),        // path, old_value, new_value
+    TypeChanged(String, String, String),     // path, old_value, new_value
+    KeyOrderChanged(String, String, String), // path, old_order, new_order
 }
 
 impl From<&DiffResult> for LightweightDiffResult {
@@ -43,6 +45,11 @@ impl From<&DiffResult> for LightweightDiffResult {
             DiffResult::TypeChanged(path, old, new) => {
                 LightweightDiffResult::TypeChanged(path.clone(), old.to_string(), new.to_string())
             }
+            DiffResult::KeyOrderChanged(path, old, new) => LightweightDiffResult::KeyOrderChanged(
+                path.clone(),
+                old.to_string(),
+                new.to_string(),
+            ),
         }
     }
 }
@@ -52,11 +59,25 @@ impl From<&DiffResult> for LightweightDiffResult {
 pub struct DiffConfig {
     pub ignore_keys_regex: Option<regex::Regex>,
     pub epsilon: Option<f64>,
+    /// Match array elements by this key instead of by position; several
+    /// comma-separated keys form a composite key, e.g. `id,region`
     pub array_id_key: Option<String>,
+    /// Keys for the arrays at particular paths, used there instead of
+    /// `array_id_key`, e.g. each SQLite table's primary key (see [`sqlite_row_keys`])
+    pub array_id_keys: BTreeMap<String, Vec<String>>,
     pub use_memory_optimization: bool, // Explicit choice
     pub batch_size: usize,
     pub ignore_whitespace: bool,
     pub ignore_case: bool,
+    /// Report objects whose common keys appear in a different order
+    pub check_key_order: bool,
+    /// Decode JSON or YAML held in strings whose path matches, see
+    /// [`decode_embedded_strings`]
+    pub decode_embedded: Option<regex::Regex>,
+    /// Decode base64 strings whose path matches, see [`decode_base64_strings`]
+    pub decode_base64: Option<regex::Regex>,
+    /// Keep text decoded from base64 in cleartext instead of redacting it
+    pub show_secrets: bool,
 }
 
 impl Default for DiffConfig {
@@ -65,10 +86,47 @@ impl Default for DiffConfig {
             ignore_keys_regex: None,
             epsilon: None,
             array_id_key: None,
+            array_id_keys: BTreeMap::new(),
             use_memory_optimization: false, // Conservative default
             batch_size: 1000,
             ignore_whitespace: false,
             ignore_case: false,
+            check_key_order: false,
+            decode_embedded: None,
+            decode_base64: None,
+            show_secrets: false,
+        }
+    }
+}
+
+/// The keys that identify array elements, by array path
+#[derive(Clone, Copy)]
+struct ArrayIdKeys<'a> {
+    default: Option<&'a str>,
+    by_path: Option<&'a BTreeMap<String, Vec<String>>>,
+}
+
+impl<'a> ArrayIdKeys<'a> {
+    fn new(array_id_key: Option<&'a str>) -> Self {
+        Self {
+            default: array_id_key,
+            by_path: None,
+        }
+    }
+
+    fn from_config(config: &'a DiffConfig) -> Self {
+        Self {
+            default: config.array_id_key.as_deref(),
+            by_path: Some(&config.array_id_keys),
+        }
+    }
+
+    fn at(&self, path: &str) -> Option<Vec<&'a str>> {
+        match self.by_path.and_then(|by_path| by_path.get(path)) {
+            Some(keys) => Some(keys.iter().map(String::as_str).collect()),
+            None => self
+                .default
+                .map(|keys| keys.split(',').map(str::trim).collect()),
         }
     }
 }
@@ -88,7 +146,8 @@ pub fn diff_standard(
         v2,
         ignore_keys_regex,
         epsilon,
-        array_id_key,
+        ArrayIdKeys::new(array_id_key),
+        false,
         false,
         false,
     )
@@ -101,35 +160,34 @@ pub fn diff_standard_with_config(v1: &Value, v2: &Value, config: &DiffConfig) ->
         v2,
         config.ignore_keys_regex.as_ref(),
         config.epsilon,
-        config.array_id_key.as_deref(),
+        ArrayIdKeys::from_config(config),
         config.ignore_whitespace,
         config.ignore_case,
+        config.check_key_order,
     )
 }
 
 /// Standard diff function - clean, predictable output
+#[allow(clippy::too_many_arguments)]
 fn diff_standard_implementation(
     v1: &Value,
     v2: &Value,
     ignore_keys_regex: Option<&Regex>,
     epsilon: Option<f64>,
-    array_id_key: Option<&str>,
+    array_ids: ArrayIdKeys,
     ignore_whitespace: bool,
     ignore_case: bool,
+    check_key_order: bool,
 ) -> Vec<DiffResult> {
     let mut results = Vec::new();
+    let (v1, v2) = decoded_pair(v1, v2);
 
     // Handle root level type or value change first
-    if !values_are_equal_with_config(v1, v2, epsilon, ignore_whitespace, ignore_case) {
-        let type_match = matches!(
-            (v1, v2),
-            (Value::Null, Value::Null)
-                | (Value::Bool(_), Value::Bool(_))
-                | (Value::Number(_), Value::Number(_))
-                | (Value::String(_), Value::String(_))
-                | (Value::Array(_), Value::Array(_))
-                | (Value::Object(_), Value::Object(_))
-        );
+    // (object equality ignores key order, so reordering needs a full walk)
+    if check_key_order && is_nested_pair(v1, v2)
+        || !values_are_equal_with_config(v1, v2, epsilon, ignore_whitespace, ignore_case)
+    {
+        let type_match = value_type_name(v1) == value_type_name(v2);
 
         if !type_match {
             results.push(DiffResult::TypeChanged(
@@ -138,7 +196,7 @@ fn diff_standard_implementation(
                 v2.clone(),
             ));
             return results;
-        } else if v1.is_object() && v2.is_object() {
+        } else if is_nested_pair(v1, v2) && v1.is_object() {
             diff_objects(
                 "",
                 v1.as_object().unwrap(),
@@ -146,9 +204,10 @@ fn diff_standard_implementation(
                 &mut results,
                 ignore_keys_regex,
                 epsilon,
-                array_id_key,
+                array_ids,
                 ignore_whitespace,
                 ignore_case,
+                check_key_order,
             );
         } else if v1.is_array() && v2.is_array() {
             diff_arrays(
@@ -158,9 +217,10 @@ fn diff_standard_implementation(
                 &mut results,
                 ignore_keys_regex,
                 epsilon,
-                array_id_key,
+                array_ids,
                 ignore_whitespace,
                 ignore_case,
+                check_key_order,
             );
         } else {
             results.push(DiffResult::Modified("".to_string(), v1.clone(), v2.clone()));
@@ -185,7 +245,8 @@ pub fn diff_optimized(
         &mut results,
         ignore_keys_regex,
         epsilon,
-        array_id_key,
+        ArrayIdKeys::new(array_id_key),
+        false,
         false,
         false,
     );
@@ -201,15 +262,35 @@ pub fn diff_optimized_with_config(v1: &Value, v2: &Value, config: &DiffConfig) -
         &mut results,
         config.ignore_keys_regex.as_ref(),
         config.epsilon,
-        config.array_id_key.as_deref(),
+        ArrayIdKeys::from_config(config),
         config.ignore_whitespace,
         config.ignore_case,
+        config.check_key_order,
     );
     results
 }
 
 /// Enhanced diff function with explicit configuration
 pub fn diff_with_config(v1: &Value, v2: &Value, config: &DiffConfig) -> Vec<DiffResult> {
+    if config.decode_embedded.is_some() || config.decode_base64.is_some() {
+        let decode = |value: &Value| {
+            let mut value = value.clone();
+            if let Some(paths) = &config.decode_base64 {
+                value = decode_base64_strings(&value, paths, config.show_secrets);
+            }
+            if let Some(paths) = &config.decode_embedded {
+                value = decode_embedded_strings(&value, paths);
+            }
+            value
+        };
+        let config = DiffConfig {
+            decode_embedded: None,
+            decode_base64: None,
+            ..config.clone()
+        };
+        return diff_with_config(&decode(v1), &decode(v2), &config);
+    }
+
     // Explicit choice: user decides which algorithm to use
     if config.use_memory_optimization {
         diff_optimized_with_config(v1, v2, config)
@@ -238,10 +319,12 @@ fn diff_recursive(
     results: &mut Vec<DiffResult>,
     ignore_keys_regex: Option<&Regex>,
     epsilon: Option<f64>,
-    array_id_key: Option<&str>,
+    array_ids: ArrayIdKeys,
     ignore_whitespace: bool,
     ignore_case: bool,
+    check_key_order: bool,
 ) {
+    let (v1, v2) = decoded_pair(v1, v2);
     match (v1, v2) {
         (Value::Object(map1), Value::Object(map2)) => {
             diff_objects(
@@ -251,9 +334,10 @@ fn diff_recursive(
                 results,
                 ignore_keys_regex,
                 epsilon,
-                array_id_key,
+                array_ids,
                 ignore_whitespace,
                 ignore_case,
+                check_key_order,
             );
         }
         (Value::Array(arr1), Value::Array(arr2)) => {
@@ -264,9 +348,10 @@ fn diff_recursive(
                 results,
                 ignore_keys_regex,
                 epsilon,
-                array_id_key,
+                array_ids,
                 ignore_whitespace,
                 ignore_case,
+                check_key_order,
             );
         }
         _ => { /* Should not happen if called correctly from diff_objects/diff_arrays */ }
@@ -281,28 +366,30 @@ fn diff_objects(
     results: &mut Vec<DiffResult>,
     ignore_keys_regex: Option<&Regex>,
     epsilon: Option<f64>,
-    array_id_key: Option<&str>,
+    array_ids: ArrayIdKeys,
     ignore_whitespace: bool,
     ignore_case: bool,
+    check_key_order: bool,
 ) {
-    // Check for modified or removed keys
-    for (key, value1) in map1 {
-        let current_path = if path.is_empty() {
-            key.clone()
-        } else {
-            format!("{path}.{key}")
+    // Walk keys in document order so added keys are reported where they appear
+    for key in ordered_key_union(map1, map2) {
+        let current_path = join_key_path(path, key);
+        let value1 = match map1.get(key) {
+            Some(value1) => value1,
+            None => {
+                results.push(DiffResult::Added(current_path, map2[key].clone()));
+                continue;
+            }
         };
         if let Some(regex) = ignore_keys_regex {
-            if regex.is_match(key) {
+            if regex.is_match(unescape_reserved_key(key)) {
                 continue;
             }
         }
         match map2.get(key) {
             Some(value2) => {
                 // Recurse for nested objects/arrays
-                if value1.is_object() && value2.is_object()
-                    || value1.is_array() && value2.is_array()
-                {
+                if is_nested_pair(value1, value2) {
                     diff_recursive(
                         &current_path,
                         value1,
@@ -310,9 +397,10 @@ fn diff_objects(
                         results,
                         ignore_keys_regex,
                         epsilon,
-                        array_id_key,
+                        array_ids,
                         ignore_whitespace,
                         ignore_case,
+                        check_key_order,
                     );
                 } else if !values_are_equal_with_config(
                     value1,
@@ -321,15 +409,7 @@ fn diff_objects(
                     ignore_whitespace,
                     ignore_case,
                 ) {
-                    let type_match = matches!(
-                        (value1, value2),
-                        (Value::Null, Value::Null)
-                            | (Value::Bool(_), Value::Bool(_))
-                            | (Value::Number(_), Value::Number(_))
-                            | (Value::String(_), Value::String(_))
-                            | (Value::Array(_), Value::Array(_))
-                            | (Value::Object(_), Value::Object(_))
-                    );
+                    let type_match = value_type_name(value1) == value_type_name(value2);
 
                     if !type_match {
                         results.push(DiffResult::TypeChanged(
@@ -352,17 +432,85 @@ fn diff_objects(
         }
     }
 
-    // Check for added keys
-    for (key, value2) in map2 {
-        if !map1.contains_key(key) {
-            let current_path = if path.is_empty() {
-                (*key).clone()
-            } else {
-                format!("{path}.{key}")
-            };
-            results.push(DiffResult::Added(current_path, value2.clone()));
+    if check_key_order {
+        check_object_key_order(path, map1, map2, results, ignore_keys_regex);
+    }
+}
+
+/// Path of an object member: `key` under `path`, with the key as in the input
+fn join_key_path(path: &str, key: &str) -> String {
+    join_yaml_path(path, unescape_reserved_key(key))
+}
+
+/// Merge the keys of two objects in document order: keys of `map1` in their
+/// order, with keys only present in `map2` placed after the shared key they
+/// follow in `map2`
+fn ordered_key_union<'a>(
+    map1: &'a serde_json::Map<String, Value>,
+    map2: &'a serde_json::Map<String, Value>,
+) -> Vec<&'a String> {
+    let mut leading: Vec<&String> = Vec::new();
+    let mut following: HashMap<&str, Vec<&String>> = HashMap::new();
+    let mut anchor: Option<&str> = None;
+
+    for key in map2.keys() {
+        if map1.contains_key(key) {
+            anchor = Some(key.as_str());
+        } else if let Some(anchor) = anchor {
+            following.entry(anchor).or_default().push(key);
+        } else {
+            leading.push(key);
+        }
+    }
+
+    let mut keys = leading;
+    keys.reserve(map1.len());
+    for key in map1.keys() {
+        keys.push(key);
+        if let Some(added) = following.remove(key.as_str()) {
+            keys.extend(added);
         }
     }
+    keys
+}
+
+/// Report when the keys shared by both objects appear in a different order
+fn check_object_key_order(
+    path: &str,
+    map1: &serde_json::Map<String, Value>,
+    map2: &serde_json::Map<String, Value>,
+    results: &mut Vec<DiffResult>,
+    ignore_keys_regex: Option<&Regex>,
+) {
+    let is_compared = |key: &String| -> bool {
+        ignore_keys_regex.map_or(true, |regex| !regex.is_match(unescape_reserved_key(key)))
+    };
+    let order1: Vec<&String> = map1
+        .keys()
+        .filter(|key| map2.contains_key(*key) && is_compared(key))
+        .collect();
+    let order2: Vec<&String> = map2
+        .keys()
+        .filter(|key| map1.contains_key(*key) && is_compared(key))
+        .collect();
+
+    if order1 != order2 {
+        results.push(DiffResult::KeyOrderChanged(
+            path.to_string(),
+            Value::Array(
+                order1
+                    .into_iter()
+                    .map(|k| Value::from(unescape_reserved_key(k)))
+                    .collect(),
+            ),
+            Value::Array(
+                order2
+                    .into_iter()
+                    .map(|k| Value::from(unescape_reserved_key(k)))
+                    .collect(),
+            ),
+        ));
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -373,38 +521,46 @@ fn diff_arrays(
     results: &mut Vec<DiffResult>,
     ignore_keys_regex: Option<&Regex>,
     epsilon: Option<f64>,
-    array_id_key: Option<&str>,
+    array_ids: ArrayIdKeys,
     ignore_whitespace: bool,
     ignore_case: bool,
+    check_key_order: bool,
 ) {
-    if let Some(id_key) = array_id_key {
-        let mut map1: HashMap<Value, &Value> = HashMap::new();
+    if let Some(id_keys) = array_ids.at(path) {
+        let mut map1: HashMap<Vec<&Value>, &Value> = HashMap::new();
+        let mut ids1: Vec<Vec<&Value>> = Vec::new();
         let mut no_id_elements1: Vec<(usize, &Value)> = Vec::new();
         for (i, val) in arr1.iter().enumerate() {
-            if let Some(id_val) = val.get(id_key) {
-                map1.insert(id_val.clone(), val);
+            if let Some(id_val) = element_id(val, &id_keys) {
+                if map1.insert(id_val.clone(), val).is_none() {
+                    ids1.push(id_val);
+                }
             } else {
                 no_id_elements1.push((i, val));
             }
         }
 
-        let mut map2: HashMap<Value, &Value> = HashMap::new();
+        let mut map2: HashMap<Vec<&Value>, &Value> = HashMap::new();
+        let mut ids2: Vec<Vec<&Value>> = Vec::new();
         let mut no_id_elements2: Vec<(usize, &Value)> = Vec::new();
         for (i, val) in arr2.iter().enumerate() {
-            if let Some(id_val) = val.get(id_key) {
-                map2.insert(id_val.clone(), val);
+            if let Some(id_val) = element_id(val, &id_keys) {
+                if map2.insert(id_val.clone(), val).is_none() {
+                    ids2.push(id_val);
+                }
             } else {
                 no_id_elements2.push((i, val));
             }
         }
 
-        // Check for modified or removed elements
-        for (id_val, val1) in &map1 {
-            let current_path = format!("{path}[{id_key}={id_val}]");
+        // Check for modified or removed elements, in array order
+        for id_val in &ids1 {
+            let val1 = &map1[id_val];
+            let current_path = element_id_path(path, &id_keys, id_val);
             match map2.get(id_val) {
                 Some(val2) => {
                     // Recurse for nested objects/arrays
-                    if val1.is_object() && val2.is_object() || val1.is_array() && val2.is_array() {
+                    if is_nested_pair(val1, val2) {
                         diff_recursive(
                             &current_path,
                             val1,
@@ -412,9 +568,10 @@ fn diff_arrays(
                             results,
                             ignore_keys_regex,
                             epsilon,
-                            array_id_key,
+                            array_ids,
                             ignore_whitespace,
                             ignore_case,
+                            check_key_order,
                         );
                     } else if !values_are_equal_with_config(
                         val1,
@@ -423,15 +580,7 @@ fn diff_arrays(
                         ignore_whitespace,
                         ignore_case,
                     ) {
-                        let type_match = matches!(
-                            (val1, val2),
-                            (Value::Null, Value::Null)
-                                | (Value::Bool(_), Value::Bool(_))
-                                | (Value::Number(_), Value::Number(_))
-                                | (Value::String(_), Value::String(_))
-                                | (Value::Array(_), Value::Array(_))
-                                | (Value::Object(_), Value::Object(_))
-                        );
+                        let type_match = value_type_name(val1) == value_type_name(val2);
 
                         if !type_match {
                             results.push(DiffResult::TypeChanged(
@@ -455,10 +604,10 @@ fn diff_arrays(
         }
 
         // Check for added elements with ID
-        for (id_val, val2) in map2 {
-            if !map1.contains_key(&id_val) {
-                let current_path = format!("{path}[{id_key}={id_val}]");
-                results.push(DiffResult::Added(current_path, val2.clone()));
+        for id_val in &ids2 {
+            if !map1.contains_key(id_val) {
+                let current_path = element_id_path(path, &id_keys, id_val);
+                results.push(DiffResult::Added(current_path, map2[id_val].clone()));
             }
         }
 
@@ -468,7 +617,7 @@ fn diff_arrays(
             match (no_id_elements1.get(i), no_id_elements2.get(i)) {
                 (Some((idx1, val1)), Some((_idx2, val2))) => {
                     let current_path = format!("{path}[{idx1}]");
-                    if val1.is_object() && val2.is_object() || val1.is_array() && val2.is_array() {
+                    if is_nested_pair(val1, val2) {
                         diff_recursive(
                             &current_path,
                             val1,
@@ -476,9 +625,10 @@ fn diff_arrays(
                             results,
                             ignore_keys_regex,
                             epsilon,
-                            array_id_key,
+                            array_ids,
                             ignore_whitespace,
                             ignore_case,
+                            check_key_order,
                         );
                     } else if !values_are_equal_with_config(
                         val1,
@@ -487,15 +637,7 @@ fn diff_arrays(
                         ignore_whitespace,
                         ignore_case,
                     ) {
-                        let type_match = matches!(
-                            (val1, val2),
-                            (Value::Null, Value::Null)
-                                | (Value::Bool(_), Value::Bool(_))
-                                | (Value::Number(_), Value::Number(_))
-                                | (Value::String(_), Value::String(_))
-                                | (Value::Array(_), Value::Array(_))
-                                | (Value::Object(_), Value::Object(_))
-                        );
+                        let type_match = value_type_name(val1) == value_type_name(val2);
 
                         if !type_match {
                             results.push(DiffResult::TypeChanged(
@@ -531,7 +673,7 @@ fn diff_arrays(
             match (arr1.get(i), arr2.get(i)) {
                 (Some(val1), Some(val2)) => {
                     // Recurse for nested objects/arrays within arrays
-                    if val1.is_object() && val2.is_object() || val1.is_array() && val2.is_array() {
+                    if is_nested_pair(val1, val2) {
                         diff_recursive(
                             &current_path,
                             val1,
@@ -539,9 +681,10 @@ fn diff_arrays(
                             results,
                             ignore_keys_regex,
                             epsilon,
-                            array_id_key,
+                            array_ids,
                             ignore_whitespace,
                             ignore_case,
+                            check_key_order,
                         );
                     } else if !values_are_equal_with_config(
                         val1,
@@ -550,15 +693,7 @@ fn diff_arrays(
                         ignore_whitespace,
                         ignore_case,
                     ) {
-                        let type_match = matches!(
-                            (val1, val2),
-                            (Value::Null, Value::Null)
-                                | (Value::Bool(_), Value::Bool(_))
-                                | (Value::Number(_), Value::Number(_))
-                                | (Value::String(_), Value::String(_))
-                                | (Value::Array(_), Value::Array(_))
-                                | (Value::Object(_), Value::Object(_))
-                        );
+                        let type_match = value_type_name(val1) == value_type_name(val2);
 
                         if !type_match {
                             results.push(DiffResult::TypeChanged(
@@ -587,6 +722,30 @@ fn diff_arrays(
     }
 }
 
+/// Values of the identity keys of an array element, or None when one is missing.
+/// Elements that are arrays themselves, such as CSV rows without headers, are
+/// identified by 0-based indexes.
+fn element_id<'a>(element: &'a Value, id_keys: &[&str]) -> Option<Vec<&'a Value>> {
+    id_keys
+        .iter()
+        .map(|key| match element {
+            Value::Array(_) => element.get(key.parse::<usize>().ok()?),
+            _ => element.get(key),
+        })
+        .collect()
+}
+
+/// Path of an array element matched by identity, e.g. `users[id=1]` or
+/// `rows[id=42,region="eu"]` for a composite key
+fn element_id_path(path: &str, id_keys: &[&str], id_val: &[&Value]) -> String {
+    let parts: Vec<String> = id_keys
+        .iter()
+        .zip(id_val)
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    format!("{path}[{}]", parts.join(","))
+}
+
 fn values_are_equal_with_config(
     v1: &Value,
     v2: &Value,
@@ -600,6 +759,17 @@ fn values_are_equal_with_config(
             return (f1 - f2).abs() < e;
         }
     }
+    if let (Value::Number(n1), Value::Number(n2)) = (v1, v2) {
+        return numbers_equal(n1, n2);
+    }
+
+    match (extension_value(v1), extension_value(v2)) {
+        (Some(ext1), Some(ext2)) => {
+            return extension_values_equal(ext1, ext2, epsilon, ignore_whitespace, ignore_case)
+        }
+        (Some(_), None) | (None, Some(_)) => return false,
+        (None, None) => {}
+    }
 
     // Handle string comparisons with ignore options
     if let (Value::String(s1), Value::String(s2)) = (v1, v2) {
@@ -629,12 +799,267 @@ fn values_are_equal_with_config(
     v1 == v2
 }
 
+/// Replace strings that hold a serialized JSON or YAML object or array with their
+/// decoded contents, so that a change inside is reported at its own path, e.g.
+/// `data["config.json"].logging.level`, instead of as one modified string.
+///
+/// Only strings whose path matches `paths` are decoded, and strings found inside
+/// decoded contents are decoded in turn. Decoded contents are stored as encoded
+/// values (see [`encoded_value`]), which keep the original format and are shown as
+/// e.g. `(json) {"level":"info"}` when added or removed as a whole. A string is read
+/// as JSON when it starts with `{` or `[`, and as YAML when it spans several lines.
+pub fn decode_embedded_strings(value: &Value, paths: &Regex) -> Value {
+    decode_embedded_at("", value, paths)
+}
+
+fn decode_embedded_at(path: &str, value: &Value, paths: &Regex) -> Value {
+    match value {
+        Value::String(s) if paths.is_match(path) => match decode_embedded_string(s) {
+            Some((format, decoded)) => {
+                encoded_value(format, decode_embedded_at(path, &decoded, paths))
+            }
+            None => value.clone(),
+        },
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| decode_embedded_at(&format!("{path}[{i}]"), item, paths))
+                .collect(),
+        ),
+        Value::Object(map) if extension_value(value).is_none() => Value::Object(
+            map.iter()
+                .map(|(key, item)| {
+                    let item_path = join_key_path(path, key);
+                    (key.clone(), decode_embedded_at(&item_path, item, paths))
+                })
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+fn decode_embedded_string(s: &str) -> Option<(&'static str, Value)> {
+    let trimmed = s.trim();
+    let decoded = if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        parse_json(trimmed).ok().map(|decoded| ("json", decoded))
+    } else {
+        None
+    };
+    let decoded = decoded.or_else(|| {
+        if !trimmed.contains('\n') {
+            return None;
+        }
+        let mut documents = parse_yaml_documents(s).ok()?;
+        (documents.len() == 1).then(|| ("yaml", documents.remove(0)))
+    })?;
+    // Scalars gain nothing from decoding, and extension values are not containers
+    let is_container = match &decoded.1 {
+        Value::Array(_) => true,
+        Value::Object(_) => extension_value(&decoded.1).is_none(),
+        _ => false,
+    };
+    is_container.then_some(decoded)
+}
+
+/// Replace base64 strings whose path matches `paths` with their decoded contents,
+/// stored as encoded values (see [`encoded_value`]) with the format `base64`.
+///
+/// Decoded JSON or YAML objects and arrays are walked into like
+/// [`decode_embedded_strings`], decoded text becomes a string, and binary data a
+/// summary of its size and SHA-256 hash, e.g. `"<binary 1024 bytes sha256:9f86d081884c>"`.
+/// A JSON Web Token (`header.payload.signature`) is decoded into its header, payload
+/// and signature, with the format `jwt`.
+///
+/// Ordinary words are valid base64 too (`"prod"`, `"web1"`), so only strings of at
+/// least [`MIN_BASE64_LEN`] characters that are the canonical encoding of their bytes
+/// are decoded, and binary data is only accepted where `paths` matches the whole
+/// path (`^data\["logo\.png"\]$`, `^data.*`) rather than a prefix of it.
+///
+/// Unless `show_secrets` is set, decoded strings and numbers are redacted to a hash
+/// as well (`"<redacted sha256:5e884898da28>"`), so that changes are detected without
+/// printing the secrets they hold.
+pub fn decode_base64_strings(value: &Value, paths: &Regex, show_secrets: bool) -> Value {
+    let whole_paths =
+        Regex::new(&format!("^(?:{})$", paths.as_str())).unwrap_or_else(|_| paths.clone());
+    let selection = Base64Paths {
+        paths,
+        whole_paths: &whole_paths,
+    };
+    decode_base64_at("", value, &selection, show_secrets)
+}
+
+/// Shortest string taken for base64 by [`decode_base64_strings`], outside of JWTs
+pub const MIN_BASE64_LEN: usize = 8;
+
+/// Paths selected for base64 decoding, and those selected in full
+struct Base64Paths<'a> {
+    paths: &'a Regex,
+    whole_paths: &'a Regex,
+}
+
+fn decode_base64_at(
+    path: &str,
+    value: &Value,
+    selection: &Base64Paths,
+    show_secrets: bool,
+) -> Value {
+    match value {
+        Value::String(s) if selection.paths.is_match(path) => {
+            let allow_binary = selection.whole_paths.is_match(path);
+            decode_base64_string(s, allow_binary, show_secrets).unwrap_or_else(|| value.clone())
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    decode_base64_at(&format!("{path}[{i}]"), item, selection, show_secrets)
+                })
+                .collect(),
+        ),
+        Value::Object(map) if extension_value(value).is_none() => Value::Object(
+            map.iter()
+                .map(|(key, item)| {
+                    let item_path = join_key_path(path, key);
+                    (
+                        key.clone(),
+                        decode_base64_at(&item_path, item, selection, show_secrets),
+                    )
+                })
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+fn decode_base64_string(s: &str, allow_binary: bool, show_secrets: bool) -> Option<Value> {
+    let trimmed = s.trim();
+    let segments: Vec<&str> = trimmed.split('.').collect();
+    if let [header, payload, signature] = segments.as_slice() {
+        let json_segment = |segment: &str| -> Option<Value> {
+            let decoded = parse_json(std::str::from_utf8(&decode_base64(segment)?).ok()?).ok()?;
+            match decoded {
+                Value::Object(_) if show_secrets => Some(decoded),
+                Value::Object(_) => Some(redact(&decoded)),
+                _ => None,
+            }
+        };
+        let mut token = serde_json::Map::new();
+        token.insert("header".to_string(), json_segment(header)?);
+        token.insert("payload".to_string(), json_segment(payload)?);
+        token.insert(
+            "signature".to_string(),
+            Value::String(binary_summary(&decode_base64(signature)?)),
+        );
+        return Some(encoded_value("jwt", Value::Object(token)));
+    }
+
+    if trimmed.len() < MIN_BASE64_LEN {
+        return None;
+    }
+    let bytes = decode_base64(trimmed)?;
+    let decoded = match String::from_utf8(bytes) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+            let decoded = match decode_embedded_string(&text) {
+                Some((format, structured)) => encoded_value(format, structured),
+                None => Value::String(text),
+            };
+            if show_secrets {
+                decoded
+            } else {
+                redact(&decoded)
+            }
+        }
+        Ok(text) if allow_binary => Value::String(binary_summary(text.as_bytes())),
+        Err(e) if allow_binary => Value::String(binary_summary(e.as_bytes())),
+        _ => return None,
+    };
+    Some(encoded_value("base64", decoded))
+}
+
+/// Decode standard or URL-safe base64, with or without padding. Only the canonical
+/// encoding of the decoded bytes is accepted: no stray padding or trailing bits.
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+    use base64::Engine;
+
+    let unpadded = s.trim_end_matches('=');
+    let padding = s.len() - unpadded.len();
+    if unpadded.is_empty() || padding > 2 || padding > 0 && s.len() % 4 != 0 {
+        return None;
+    }
+    [STANDARD_NO_PAD, URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| {
+            let bytes = engine.decode(unpadded).ok()?;
+            (engine.encode(&bytes) == unpadded).then_some(bytes)
+        })
+}
+
+fn short_sha256(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(bytes)[..6]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn binary_summary(bytes: &[u8]) -> String {
+    format!(
+        "<binary {} bytes sha256:{}>",
+        bytes.len(),
+        short_sha256(bytes)
+    )
+}
+
+/// Replace strings and numbers with a hash of their text, keeping keys and structure
+fn redact(value: &Value) -> Value {
+    match extension_value(value) {
+        Some(ExtensionValue::Encoded(format, decoded)) => {
+            return encoded_value(format, redact(decoded))
+        }
+        Some(_) => {
+            return Value::String(format!(
+                "<redacted sha256:{}>",
+                short_sha256(format_value(value).as_bytes())
+            ))
+        }
+        None => {}
+    }
+    match value {
+        Value::String(s) => {
+            Value::String(format!("<redacted sha256:{}>", short_sha256(s.as_bytes())))
+        }
+        Value::Number(n) => Value::String(format!(
+            "<redacted sha256:{}>",
+            short_sha256(n.to_string().as_bytes())
+        )),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), redact(item)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
 fn normalize_whitespace(s: &str) -> String {
     // Replace all whitespace sequences with single spaces and trim
     s.split_whitespace().collect::<Vec<&str>>().join(" ")
 }
 
 pub fn value_type_name(value: &Value) -> &str {
+    match extension_value(value) {
+        Some(ExtensionValue::DateTime(_)) => return "DateTime",
+        Some(ExtensionValue::SpecialFloat(_)) => return "Number",
+        Some(ExtensionValue::Tagged(..)) => return "Tagged",
+        Some(ExtensionValue::Bytes(_)) => return "Bytes",
+        Some(ExtensionValue::Encoded(..)) => return "Encoded",
+        None => {}
+    }
     match value {
         Value::Null => "Null",
         Value::Bool(_) => "Boolean",
@@ -645,6 +1070,386 @@ pub fn value_type_name(value: &Value) -> &str {
     }
 }
 
+// Extension values: scalars that JSON cannot express are kept as single-key objects
+// under reserved keys, so that they survive the serde_json value model and can be
+// compared and printed in their own terms.
+
+/// Marker key of a date/time, e.g. TOML `1979-05-27T07:32:00Z`
+pub const DATETIME_KEY: &str = "$__diffx_datetime";
+/// Marker key of a non-finite float: `nan`, `inf` or `-inf`
+pub const SPECIAL_FLOAT_KEY: &str = "$__diffx_float";
+/// Marker key of a tagged value stored as `[tag, value]`, e.g. YAML `!Ref bucket`
+pub const TAGGED_KEY: &str = "$__diffx_tagged";
+/// Marker key of a binary blob, stored encoded as `base64:...` or `hex:...`
+pub const BYTES_KEY: &str = "$__diffx_bytes";
+/// Marker key of a string holding serialized data, stored decoded as `[format, value]`
+pub const ENCODED_KEY: &str = "$__diffx_encoded";
+
+/// A scalar stored with one of the reserved marker keys
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExtensionValue<'a> {
+    DateTime(&'a str),
+    SpecialFloat(&'a str),
+    Tagged(&'a str, &'a Value),
+    Bytes(&'a str),
+    Encoded(&'a str, &'a Value),
+}
+
+pub fn datetime_value(datetime: impl Into<String>) -> Value {
+    single_key_object(DATETIME_KEY, Value::String(datetime.into()))
+}
+
+/// Store a float, using the special-float marker when it is NaN or infinite
+pub fn float_value(float: f64) -> Value {
+    match serde_json::Number::from_f64(float) {
+        Some(number) => Value::Number(number),
+        None if float.is_nan() => single_key_object(SPECIAL_FLOAT_KEY, Value::from("nan")),
+        None if float > 0.0 => single_key_object(SPECIAL_FLOAT_KEY, Value::from("inf")),
+        None => single_key_object(SPECIAL_FLOAT_KEY, Value::from("-inf")),
+    }
+}
+
+pub fn tagged_value(tag: impl Into<String>, value: Value) -> Value {
+    single_key_object(
+        TAGGED_KEY,
+        Value::Array(vec![Value::String(tag.into()), value]),
+    )
+}
+
+/// Store a binary blob, encoded as base64 or, with `as_hex`, as lowercase hex
+pub fn bytes_value(bytes: &[u8], as_hex: bool) -> Value {
+    use base64::Engine;
+
+    let encoded = if as_hex {
+        let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!("hex:{hex}")
+    } else {
+        format!(
+            "base64:{}",
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        )
+    };
+    single_key_object(BYTES_KEY, Value::String(encoded))
+}
+
+/// Store the decoded contents of a string that held `format` (`json`, `yaml`)
+pub fn encoded_value(format: impl Into<String>, value: Value) -> Value {
+    single_key_object(
+        ENCODED_KEY,
+        Value::Array(vec![Value::String(format.into()), value]),
+    )
+}
+
+fn single_key_object(key: &str, value: Value) -> Value {
+    let mut map = serde_json::Map::new();
+    map.insert(key.to_string(), value);
+    Value::Object(map)
+}
+
+/// Recognise a value stored with a reserved marker key
+pub fn extension_value(value: &Value) -> Option<ExtensionValue<'_>> {
+    let map = value.as_object()?;
+    if map.len() != 1 {
+        return None;
+    }
+    let (key, inner) = map.iter().next()?;
+    match key.as_str() {
+        DATETIME_KEY => inner.as_str().map(ExtensionValue::DateTime),
+        SPECIAL_FLOAT_KEY => inner.as_str().map(ExtensionValue::SpecialFloat),
+        TAGGED_KEY => match inner.as_array()?.as_slice() {
+            [Value::String(tag), tagged] => Some(ExtensionValue::Tagged(tag, tagged)),
+            _ => None,
+        },
+        BYTES_KEY => inner.as_str().map(ExtensionValue::Bytes),
+        ENCODED_KEY => match inner.as_array()?.as_slice() {
+            [Value::String(format), decoded] => Some(ExtensionValue::Encoded(format, decoded)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Input keys that look like marker keys (`$__diffx_float`, `$$__diffx_float`, ...)
+// get one more leading `$` when parsed, so that a plain `{"$__diffx_float": "nan"}`
+// object in the input stays an object. Paths and output show them unescaped.
+
+/// Whether a key is a run of `$` followed by the marker prefix `__diffx_`
+fn is_reserved_key(key: &str) -> bool {
+    let rest = key.trim_start_matches('$');
+    rest.len() < key.len() && rest.starts_with("__diffx_")
+}
+
+/// Escape a key read from the input so that it cannot be taken for a marker key
+pub fn escape_reserved_key(key: String) -> String {
+    if is_reserved_key(&key) {
+        format!("${key}")
+    } else {
+        key
+    }
+}
+
+/// The key as it appeared in the input, undoing [`escape_reserved_key`]
+pub fn unescape_reserved_key(key: &str) -> &str {
+    match key.strip_prefix('$') {
+        Some(rest) if is_reserved_key(rest) => rest,
+        _ => key,
+    }
+}
+
+/// Escape every object key of a value parsed from input that has no extension values
+pub fn escape_reserved_keys(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(escape_reserved_keys),
+        Value::Object(map) => {
+            map.values_mut().for_each(escape_reserved_keys);
+            if map.keys().any(|key| is_reserved_key(key)) {
+                *map = std::mem::take(map)
+                    .into_iter()
+                    .map(|(key, value)| (escape_reserved_key(key), value))
+                    .collect();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Render a value for display: JSON, except that extension values use their native
+/// syntax (`1979-05-27T07:32:00Z`, `nan`, `!Ref "bucket"`)
+pub fn format_value(value: &Value) -> String {
+    match extension_value(value) {
+        Some(ExtensionValue::DateTime(datetime)) => return datetime.to_string(),
+        Some(ExtensionValue::SpecialFloat(float)) => return float.to_string(),
+        Some(ExtensionValue::Tagged(tag, tagged)) => {
+            return format!("{tag} {}", format_value(tagged))
+        }
+        Some(ExtensionValue::Bytes(bytes)) => return bytes.to_string(),
+        Some(ExtensionValue::Encoded(format, decoded)) => {
+            return format!("({format}) {}", format_value(decoded))
+        }
+        None => {}
+    }
+
+    match value {
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(format_value).collect();
+            format!("[{}]", items.join(","))
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| {
+                    let key = unescape_reserved_key(key);
+                    format!("{}:{}", Value::from(key), format_value(value))
+                })
+                .collect();
+            format!("{{{}}}", entries.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Convert a value for YAML output, writing extension values as YAML floats and tags
+pub fn to_yaml_value(value: &Value) -> serde_yml::Value {
+    use serde_yml::value::{Tag, TaggedValue};
+    use serde_yml::Value as Yaml;
+
+    match extension_value(value) {
+        Some(ExtensionValue::DateTime(datetime)) => return Yaml::String(datetime.to_string()),
+        Some(ExtensionValue::SpecialFloat(float)) => {
+            let float = match float {
+                "nan" => f64::NAN,
+                "-inf" => f64::NEG_INFINITY,
+                _ => f64::INFINITY,
+            };
+            return Yaml::Number(float.into());
+        }
+        Some(ExtensionValue::Tagged(tag, tagged)) => {
+            return Yaml::Tagged(Box::new(TaggedValue {
+                tag: Tag::new(tag),
+                value: to_yaml_value(tagged),
+            }))
+        }
+        Some(ExtensionValue::Bytes(bytes)) => return Yaml::String(bytes.to_string()),
+        Some(ExtensionValue::Encoded(format, decoded)) => {
+            return Yaml::Tagged(Box::new(TaggedValue {
+                tag: Tag::new(format),
+                value: to_yaml_value(decoded),
+            }))
+        }
+        None => {}
+    }
+
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Bool(*b),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Yaml::Number(i.into())
+            } else if let Some(u) = n.as_u64() {
+                Yaml::Number(u.into())
+            } else if n.as_i128().is_some() || n.as_u128().is_some() {
+                // Beyond 64 bits YAML numbers would lose precision
+                Yaml::String(n.to_string())
+            } else {
+                Yaml::Number(n.as_f64().unwrap_or(f64::NAN).into())
+            }
+        }
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Sequence(items.iter().map(to_yaml_value).collect()),
+        Value::Object(map) => Yaml::Mapping(
+            map.iter()
+                .map(|(key, value)| {
+                    let key = unescape_reserved_key(key).to_string();
+                    (Yaml::String(key), to_yaml_value(value))
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Convert a value for JSON output. Date/times, non-finite floats and bytes become
+/// strings in their native syntax (`1979-05-27T07:32:00Z`, `nan`, `base64:...`), a
+/// tagged value an object keyed by its tag (`{"!Ref": "bucket"}`), and a decoded
+/// string its decoded contents.
+pub fn to_plain_json(value: &Value) -> Value {
+    match extension_value(value) {
+        Some(ExtensionValue::DateTime(text))
+        | Some(ExtensionValue::SpecialFloat(text))
+        | Some(ExtensionValue::Bytes(text)) => return Value::from(text),
+        Some(ExtensionValue::Tagged(tag, tagged)) => {
+            let mut map = serde_json::Map::new();
+            map.insert(tag.to_string(), to_plain_json(tagged));
+            return Value::Object(map);
+        }
+        Some(ExtensionValue::Encoded(_, decoded)) => return to_plain_json(decoded),
+        None => {}
+    }
+
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(to_plain_json).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let key = unescape_reserved_key(key).to_string();
+                    (key, to_plain_json(value))
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Compare numbers by value rather than spelling: `1.50` equals `1.5`, and integers
+/// beyond 64 bits are compared exactly
+fn numbers_equal(n1: &serde_json::Number, n2: &serde_json::Number) -> bool {
+    if n1 == n2 {
+        return true;
+    }
+    if let (Some(i1), Some(i2)) = (n1.as_i128(), n2.as_i128()) {
+        return i1 == i2;
+    }
+    if let (Some(u1), Some(u2)) = (n1.as_u128(), n2.as_u128()) {
+        return u1 == u2;
+    }
+    let is_integer = |n: &serde_json::Number| !n.as_str().contains(['.', 'e', 'E']);
+    if is_integer(n1) && is_integer(n2) {
+        return false;
+    }
+    matches!((n1.as_f64(), n2.as_f64()), (Some(f1), Some(f2)) if f1 == f2)
+}
+
+/// Compare date/times by the instant they denote when both carry an offset
+/// (`07:32:00Z` equals `00:32:00-07:00`), otherwise field by field
+fn datetimes_equal(a: &str, b: &str) -> bool {
+    use toml::value::{Datetime, Offset};
+
+    if a == b {
+        return true;
+    }
+    let (Ok(dt1), Ok(dt2)) = (a.parse::<Datetime>(), b.parse::<Datetime>()) else {
+        return false;
+    };
+
+    let instant = |dt: &Datetime| -> Option<(i64, u32)> {
+        let (date, time, offset) = (dt.date?, dt.time?, dt.offset?);
+        let offset_minutes = match offset {
+            Offset::Z => 0,
+            Offset::Custom { minutes } => i64::from(minutes),
+        };
+        let seconds = days_from_civil(date.year.into(), date.month.into(), date.day.into())
+            * 86_400
+            + i64::from(time.hour) * 3_600
+            + i64::from(time.minute) * 60
+            + i64::from(time.second)
+            - offset_minutes * 60;
+        Some((seconds, time.nanosecond))
+    };
+
+    match (instant(&dt1), instant(&dt2)) {
+        (Some(i1), Some(i2)) => i1 == i2,
+        _ => dt1 == dt2,
+    }
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn extension_values_equal(
+    ext1: ExtensionValue,
+    ext2: ExtensionValue,
+    epsilon: Option<f64>,
+    ignore_whitespace: bool,
+    ignore_case: bool,
+) -> bool {
+    match (ext1, ext2) {
+        (ExtensionValue::DateTime(a), ExtensionValue::DateTime(b)) => datetimes_equal(a, b),
+        (ExtensionValue::SpecialFloat(a), ExtensionValue::SpecialFloat(b)) => a == b,
+        (ExtensionValue::Bytes(a), ExtensionValue::Bytes(b)) => a == b,
+        (ExtensionValue::Tagged(tag1, v1), ExtensionValue::Tagged(tag2, v2))
+        | (ExtensionValue::Encoded(tag1, v1), ExtensionValue::Encoded(tag2, v2)) => {
+            tag1 == tag2
+                && values_are_equal_with_config(v1, v2, epsilon, ignore_whitespace, ignore_case)
+        }
+        _ => false,
+    }
+}
+
+/// Whether two values are walked into rather than compared as a whole
+fn is_nested_pair(v1: &Value, v2: &Value) -> bool {
+    match (v1, v2) {
+        (Value::Array(_), Value::Array(_)) => true,
+        (Value::Object(_), Value::Object(_)) => match (extension_value(v1), extension_value(v2)) {
+            (None, None) => true,
+            (
+                Some(ExtensionValue::Encoded(format1, inner1)),
+                Some(ExtensionValue::Encoded(format2, inner2)),
+            ) => format1 == format2 && is_nested_pair(inner1, inner2),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The decoded contents of two strings of the same encoding, which are walked into
+/// under the path of the strings; any other pair as it is
+fn decoded_pair<'a>(v1: &'a Value, v2: &'a Value) -> (&'a Value, &'a Value) {
+    match (extension_value(v1), extension_value(v2)) {
+        (
+            Some(ExtensionValue::Encoded(format1, inner1)),
+            Some(ExtensionValue::Encoded(format2, inner2)),
+        ) if format1 == format2 && is_nested_pair(inner1, inner2) => decoded_pair(inner1, inner2),
+        _ => (v1, v2),
+    }
+}
+
 /// Get approximate memory usage of a Value in bytes
 pub fn estimate_memory_usage(value: &Value) -> usize {
     match value {
@@ -676,65 +1481,4161 @@ pub fn would_exceed_memory_limit(v1: &Value, v2: &Value) -> bool {
     (usage1 + usage2) * 3 > MAX_MEMORY_USAGE
 }
 
-pub fn parse_ini(content: &str) -> Result<Value> {
-    use configparser::ini::Ini;
-
-    let mut ini = Ini::new();
-    ini.read(content.to_string())
-        .map_err(|e| anyhow!("Failed to parse INI: {}", e))?;
-
-    let mut root_map = serde_json::Map::new();
-
-    for section_name in ini.sections() {
-        let mut section_map = serde_json::Map::new();
+/// INI dialect settings. The defaults lowercase names, put keys that appear before the
+/// first section header into a `default` section and keep the last of duplicate keys.
+#[derive(Debug, Clone)]
+pub struct IniOptions {
+    /// Keep the case of section and key names instead of lowercasing them
+    pub case_sensitive: bool,
+    /// Section for keys before the first section header, or None to keep them at the top level
+    pub global_section: Option<String>,
+    /// Collect the values of a key repeated within a section into an array
+    /// (systemd `ExecStart=`, git `fetch =`) instead of keeping the last one
+    pub duplicate_keys_as_arrays: bool,
+    /// Characters that start a comment after a value when preceded by whitespace and
+    /// outside quotes; empty keeps them in the value
+    pub inline_comment_chars: Vec<char>,
+    /// Accept `key` lines without `=` or `:`, read as null
+    pub allow_no_value: bool,
+}
 
-        if let Some(section) = ini.get_map_ref().get(&section_name) {
-            for (key, value) in section {
-                if let Some(v) = value {
-                    section_map.insert(key.clone(), Value::String(v.clone()));
-                } else {
-                    section_map.insert(key.clone(), Value::Null);
-                }
-            }
+impl Default for IniOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: false,
+            global_section: Some("default".to_string()),
+            duplicate_keys_as_arrays: false,
+            inline_comment_chars: vec![';', '#'],
+            allow_no_value: true,
         }
-
-        root_map.insert(section_name, Value::Object(section_map));
     }
-
-    Ok(Value::Object(root_map))
 }
 
-pub fn parse_xml(content: &str) -> Result<Value> {
-    let value: Value = from_str(content)?;
-    Ok(value)
+pub fn parse_ini(content: &str) -> Result<Value> {
+    parse_ini_with_options(content, &IniOptions::default())
 }
 
-pub fn parse_csv(content: &str) -> Result<Value> {
-    let mut reader = ReaderBuilder::new().from_reader(content.as_bytes());
-    let mut records = Vec::new();
-
-    let headers = reader.headers()?.clone();
-    let has_headers = !headers.is_empty();
-
-    for result in reader.into_records() {
-        let record = result?;
-        if has_headers {
-            let mut obj = serde_json::Map::new();
-            for (i, header) in headers.iter().enumerate() {
-                if let Some(value) = record.get(i) {
-                    obj.insert(header.to_string(), Value::String(value.to_string()));
-                }
+/// Cut an inline comment off an INI line. A comment character only starts a comment
+/// after whitespace and outside quotes, so `url = http://h/#frag` and `password = a;b`
+/// keep their values.
+fn strip_ini_inline_comment<'a>(line: &'a str, comment_chars: &[char]) -> &'a str {
+    let mut quote = None;
+    let mut previous = None;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            // A quote only opens at the start of a word, so `don't` is not a quoted string
+            None if matches!(c, '"' | '\'')
+                && previous.map_or(true, |p: char| p.is_whitespace() || p == '=' || p == ':') =>
+            {
+                quote = Some(c)
             }
-            records.push(Value::Object(obj));
-        } else {
-            let mut arr = Vec::new();
-            for field in record.iter() {
-                arr.push(Value::String(field.to_string()));
+            None if comment_chars.contains(&c) && previous.is_some_and(char::is_whitespace) => {
+                return line[..i].trim_end();
             }
-            records.push(Value::Array(arr));
+            None => {}
         }
+        previous = Some(c);
     }
-    Ok(Value::Array(records))
+    line
+}
+
+pub fn parse_ini_with_options(content: &str, options: &IniOptions) -> Result<Value> {
+    let fold = |name: &str| -> String {
+        if options.case_sensitive {
+            name.to_string()
+        } else {
+            name.to_lowercase()
+        }
+    };
+
+    let mut root_map = serde_json::Map::new();
+    // None while reading global keys that go to the top level
+    let mut section = options.global_section.as_deref().map(fold);
+
+    for (index, raw_line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with([';', '#']) {
+            continue;
+        }
+        let line = strip_ini_inline_comment(line, &options.inline_comment_chars);
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let end = header.rfind(']').ok_or_else(|| {
+                anyhow!(
+                    "Failed to parse INI at line {}: section header has no closing bracket",
+                    line_number
+                )
+            })?;
+            let name = fold(header[..end].trim());
+            match root_map
+                .entry(name.clone())
+                .or_insert_with(|| Value::Object(serde_json::Map::new()))
+            {
+                Value::Object(_) => {}
+                _ => {
+                    return Err(anyhow!(
+                        "Failed to parse INI at line {}: section [{}] has the name of a global key",
+                        line_number,
+                        name
+                    ))
+                }
+            }
+            section = Some(name);
+            continue;
+        }
+
+        let (key, value) = match line.find(['=', ':']) {
+            Some(i) => (
+                line[..i].trim(),
+                Value::String(line[i + 1..].trim().to_string()),
+            ),
+            None if options.allow_no_value => (line, Value::Null),
+            None => {
+                return Err(anyhow!(
+                    "Failed to parse INI at line {}: expected 'key = value'",
+                    line_number
+                ))
+            }
+        };
+        if key.is_empty() {
+            return Err(anyhow!(
+                "Failed to parse INI at line {}: key cannot be empty",
+                line_number
+            ));
+        }
+        let key = fold(key);
+
+        let target = match &section {
+            Some(name) => match root_map
+                .entry(name.clone())
+                .or_insert_with(|| Value::Object(serde_json::Map::new()))
+            {
+                Value::Object(map) => map,
+                _ => {
+                    return Err(anyhow!(
+                        "Failed to parse INI at line {}: section [{}] has the name of a global key",
+                        line_number,
+                        name
+                    ))
+                }
+            },
+            None => {
+                if root_map.get(&key).is_some_and(Value::is_object) {
+                    return Err(anyhow!(
+                        "Failed to parse INI at line {}: global key '{}' has the name of a section",
+                        line_number,
+                        key
+                    ));
+                }
+                &mut root_map
+            }
+        };
+
+        match target.get_mut(&key) {
+            Some(existing) if options.duplicate_keys_as_arrays => match existing {
+                Value::Array(values) => values.push(value),
+                _ => *existing = Value::Array(vec![existing.take(), value]),
+            },
+            _ => {
+                target.insert(key, value);
+            }
+        }
+    }
+
+    let mut value = Value::Object(root_map);
+    escape_reserved_keys(&mut value);
+    Ok(value)
+}
+
+/// Parse TOML, keeping date/times and non-finite floats as extension values
+pub fn parse_toml(content: &str) -> Result<Value> {
+    let table: toml::Table = toml::from_str(content)?;
+    Ok(toml_to_json(toml::Value::Table(table)))
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => float_value(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => datetime_value(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (escape_reserved_key(key), toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Parse a YAML stream; a stream with several `---` documents becomes an array of documents
+pub fn parse_yaml(content: &str) -> Result<Value> {
+    let mut documents = parse_yaml_documents(content)?;
+    if documents.len() == 1 {
+        Ok(documents.remove(0))
+    } else {
+        Ok(Value::Array(documents))
+    }
+}
+
+/// Parse every document of a YAML stream (Kubernetes bundles, Helm template output).
+/// Empty documents between separators are skipped.
+///
+/// Aliases are expanded and `<<` merge keys are applied, so each document is the
+/// effective mapping a YAML consumer would see.
+pub fn parse_yaml_documents(content: &str) -> Result<Vec<Value>> {
+    use serde::Deserialize;
+
+    check_yaml_aliases(content)?;
+
+    let mut documents = Vec::new();
+    for document in serde_yml::Deserializer::from_str(content) {
+        let mut yaml = serde_yml::Value::deserialize(document)?;
+        apply_yaml_merge_keys(&mut yaml, "")?;
+        documents.push(yaml_to_json(yaml));
+    }
+
+    if documents.len() > 1 {
+        documents.retain(|doc| !doc.is_null());
+    }
+    if documents.is_empty() {
+        documents.push(Value::Null);
+    }
+    Ok(documents)
+}
+
+/// Reject alias cycles and alias bombs before the loader expands the aliases.
+///
+/// An alias to a node that is still open refers to itself. Otherwise the number of
+/// alias expansions is counted the way the loader replays them, and a document that
+/// expands more than 100 aliases per event of its source is rejected. Syntax errors
+/// are left to the loader, which reports them with more context.
+fn check_yaml_aliases(content: &str) -> Result<()> {
+    use yaml_rust2::Event;
+
+    let mut parser = yaml_rust2::parser::Parser::new_from_str(content);
+    // Expansions inside each anchored node, and inside the collections still open
+    let mut expansions: HashMap<usize, u64> = HashMap::new();
+    let mut open: Vec<(usize, u64)> = Vec::new();
+    let mut document_expansions = 0u64;
+    let mut document_events = 0u64;
+    let mut document_index = 0;
+
+    loop {
+        let Ok((event, marker)) = parser.next_token() else {
+            return Ok(());
+        };
+        document_events += 1;
+        let count = match event {
+            Event::StreamEnd => return Ok(()),
+            Event::DocumentStart => {
+                document_expansions = 0;
+                document_events = 1;
+                continue;
+            }
+            Event::DocumentEnd => {
+                if document_expansions > document_events.saturating_mul(100) {
+                    return Err(anyhow!(
+                        "Alias expansion limit exceeded in YAML document {} (possible alias bomb): {} aliases would be expanded",
+                        document_index,
+                        document_expansions
+                    ));
+                }
+                document_index += 1;
+                continue;
+            }
+            Event::Alias(anchor) => {
+                if open.iter().any(|&(open_anchor, _)| open_anchor == anchor) {
+                    return Err(anyhow!(
+                        "Alias cycle in YAML at line {}, column {}: the alias refers to a node that contains it",
+                        marker.line(),
+                        marker.col()
+                    ));
+                }
+                expansions
+                    .get(&anchor)
+                    .map_or(1, |inner| inner.saturating_add(1))
+            }
+            Event::Scalar(_, _, anchor, _) => {
+                if anchor > 0 {
+                    expansions.insert(anchor, 0);
+                }
+                0
+            }
+            Event::MappingStart(anchor, _) | Event::SequenceStart(anchor, _) => {
+                open.push((anchor, 0));
+                continue;
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                let (anchor, count) = open.pop().unwrap_or_default();
+                if anchor > 0 {
+                    expansions.insert(anchor, count);
+                }
+                count
+            }
+            _ => continue,
+        };
+        match open.last_mut() {
+            Some((_, total)) => *total = total.saturating_add(count),
+            None => document_expansions = document_expansions.saturating_add(count),
+        }
+    }
+}
+
+/// Apply `<<` merge keys bottom-up. Explicit keys win over merged ones, earlier
+/// sources in `<<: [*a, *b]` win over later ones, and merged keys take the place
+/// of the `<<` entry in the key order.
+fn apply_yaml_merge_keys(value: &mut serde_yml::Value, path: &str) -> Result<()> {
+    use serde_yml::{Mapping, Value as Yaml};
+
+    match value {
+        Yaml::Sequence(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                apply_yaml_merge_keys(item, &format!("{path}[{i}]"))?;
+            }
+        }
+        Yaml::Tagged(tagged) => apply_yaml_merge_keys(&mut tagged.value, path)?,
+        Yaml::Mapping(mapping) => {
+            for (key, child) in mapping.iter_mut() {
+                apply_yaml_merge_keys(child, &join_yaml_path(path, &yaml_key_to_string(key)))?;
+            }
+
+            let merge_key = Yaml::String("<<".to_string());
+            if !mapping.contains_key(&merge_key) {
+                return Ok(());
+            }
+            let location = if path.is_empty() {
+                "the document root".to_string()
+            } else {
+                format!("'{path}'")
+            };
+
+            let explicit: Vec<Yaml> = mapping
+                .keys()
+                .filter(|key| **key != merge_key)
+                .cloned()
+                .collect();
+            let mut merged = Mapping::with_capacity(mapping.len());
+            for (key, child) in std::mem::take(mapping) {
+                if key != merge_key {
+                    merged.insert(key, child);
+                    continue;
+                }
+                let sources = match child {
+                    Yaml::Mapping(source) => vec![source],
+                    Yaml::Sequence(items) => items
+                        .into_iter()
+                        .map(|item| match item {
+                            Yaml::Mapping(source) => Ok(source),
+                            _ => Err(anyhow!(
+                                "Merge key '<<' at {} must reference mappings only",
+                                location
+                            )),
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                    _ => {
+                        return Err(anyhow!(
+                            "Merge key '<<' at {} must reference a mapping or a list of mappings",
+                            location
+                        ))
+                    }
+                };
+                for source in sources {
+                    for (source_key, source_value) in source {
+                        if !explicit.contains(&source_key) && !merged.contains_key(&source_key) {
+                            merged.insert(source_key, source_value);
+                        }
+                    }
+                }
+            }
+            *mapping = merged;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Convert a loaded YAML value into the JSON value model used by the diff engine.
+/// Non-string keys use their YAML spelling; `.nan`/`.inf` and custom tags (`!Ref`)
+/// become extension values.
+fn yaml_to_json(value: serde_yml::Value) -> Value {
+    use serde_yml::Value as Yaml;
+
+    match value {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::from(i)
+            } else if let Some(u) = n.as_u64() {
+                Value::from(u)
+            } else {
+                n.as_f64().map_or(Value::Null, float_value)
+            }
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => Value::Array(items.into_iter().map(yaml_to_json).collect()),
+        Yaml::Mapping(mapping) => Value::Object(
+            mapping
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = escape_reserved_key(yaml_key_to_string(&key));
+                    (key, yaml_to_json(value))
+                })
+                .collect(),
+        ),
+        Yaml::Tagged(tagged) => tagged_value(tagged.tag.to_string(), yaml_to_json(tagged.value)),
+    }
+}
+
+fn yaml_key_to_string(key: &serde_yml::Value) -> String {
+    match key {
+        serde_yml::Value::String(s) => s.clone(),
+        serde_yml::Value::Null => "null".to_string(),
+        serde_yml::Value::Bool(b) => b.to_string(),
+        serde_yml::Value::Number(n) => n.to_string(),
+        other => serde_yml::to_string(other)
+            .map(|s| s.trim_end().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+fn join_yaml_path(path: &str, key: &str) -> String {
+    // A key such as `config.json` is bracketed, `data["config.json"].level`, so that
+    // it cannot be mistaken for two nested keys
+    if key.contains(['.', '[', ']']) {
+        format!("{path}[{}]", Value::String(key.to_string()))
+    } else if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Find which anchor the nodes of each document of a YAML stream came from.
+///
+/// The map of each document goes from a diff path to an anchor name and covers
+/// anchored nodes (`base: &base`), aliases (`env: *base`) and keys pulled in by
+/// merge keys (`<<: *base`). Documents line up with [`parse_yaml_documents`].
+pub fn yaml_anchor_sources(content: &str) -> Result<Vec<BTreeMap<String, String>>> {
+    use yaml_rust2::scanner::{Scanner, TokenType};
+
+    // The parser numbers anchors from 1 in the order they appear in the stream
+    let names: Vec<String> = Scanner::new(content.chars())
+        .filter_map(|token| match token.1 {
+            TokenType::Anchor(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+
+    let mut collector = YamlAnchorCollector {
+        names,
+        ..Default::default()
+    };
+    yaml_rust2::parser::Parser::new_from_str(content)
+        .load(&mut collector, true)
+        .map_err(|e| anyhow!("Failed to parse YAML: {}", e))?;
+
+    let mut documents = collector.documents;
+    if documents.len() > 1 {
+        documents.retain(|(_, is_null)| !is_null);
+    }
+    let mut sources: Vec<BTreeMap<String, String>> =
+        documents.into_iter().map(|(sources, _)| sources).collect();
+    if sources.is_empty() {
+        sources.push(BTreeMap::new());
+    }
+    Ok(sources)
+}
+
+/// Look up the anchor a diff path came from: the closest enclosing path with a source
+pub fn anchor_for_path<'a>(sources: &'a BTreeMap<String, String>, path: &str) -> Option<&'a str> {
+    let mut candidate = path;
+    loop {
+        if let Some(anchor) = sources.get(candidate) {
+            return Some(anchor);
+        }
+        candidate = &candidate[..candidate.rfind(['.', '['])?];
+    }
+}
+
+#[derive(Default)]
+struct YamlAnchorCollector {
+    names: Vec<String>,
+    /// Finished documents with a flag for documents that are a bare null
+    documents: Vec<(BTreeMap<String, String>, bool)>,
+    current: BTreeMap<String, String>,
+    is_null_document: bool,
+    stack: Vec<AnchorFrame>,
+    /// Effective top-level keys of anchored mappings, for resolving merges
+    mapping_keys: HashMap<usize, Vec<String>>,
+    /// Depth inside a node that has no path of its own (complex keys, inline merges)
+    skip_depth: usize,
+}
+
+enum AnchorFrame {
+    Sequence {
+        path: String,
+        index: usize,
+        /// The sequence of a `<<: [*a, *b]` entry
+        merge: bool,
+    },
+    Mapping {
+        path: String,
+        anchor: usize,
+        key: Option<String>,
+        keys: Vec<String>,
+        merges: Vec<usize>,
+    },
+}
+
+/// Where the next node of the event stream goes
+enum NodeSlot {
+    Key,
+    Value(String),
+    Merge,
+}
+
+impl YamlAnchorCollector {
+    fn anchor_name(&self, id: usize) -> Option<String> {
+        id.checked_sub(1)
+            .and_then(|index| self.names.get(index))
+            .cloned()
+    }
+
+    fn record(&mut self, path: &str, anchor: usize) {
+        if let Some(name) = self.anchor_name(anchor) {
+            if !path.is_empty() {
+                self.current.insert(path.to_string(), name);
+            }
+        }
+    }
+
+    fn next_slot(&mut self) -> NodeSlot {
+        match self.stack.last_mut() {
+            None => NodeSlot::Value(String::new()),
+            Some(AnchorFrame::Sequence { merge: true, .. }) => NodeSlot::Merge,
+            Some(AnchorFrame::Sequence { path, index, .. }) => {
+                let slot = NodeSlot::Value(format!("{path}[{index}]"));
+                *index += 1;
+                slot
+            }
+            Some(AnchorFrame::Mapping { path, key, .. }) => match key.take() {
+                None => NodeSlot::Key,
+                Some(k) if k == "<<" => NodeSlot::Merge,
+                Some(k) => NodeSlot::Value(join_yaml_path(path, &k)),
+            },
+        }
+    }
+
+    fn set_key(&mut self, new_key: String) {
+        if let Some(AnchorFrame::Mapping { key, keys, .. }) = self.stack.last_mut() {
+            if new_key != "<<" {
+                keys.push(new_key.clone());
+            }
+            *key = Some(new_key);
+        }
+    }
+
+    fn add_merge(&mut self, anchor: usize) {
+        let frame = match self.stack.last() {
+            Some(AnchorFrame::Sequence { merge: true, .. }) => self.stack.iter_mut().rev().nth(1),
+            _ => self.stack.last_mut(),
+        };
+        if let Some(AnchorFrame::Mapping { merges, .. }) = frame {
+            merges.push(anchor);
+        }
+    }
+
+    fn start_collection(&mut self, anchor: usize, is_mapping: bool) {
+        if self.skip_depth > 0 {
+            self.skip_depth += 1;
+            return;
+        }
+        let path = match self.next_slot() {
+            NodeSlot::Value(path) => path,
+            NodeSlot::Merge if !is_mapping => {
+                self.stack.push(AnchorFrame::Sequence {
+                    path: String::new(),
+                    index: 0,
+                    merge: true,
+                });
+                return;
+            }
+            NodeSlot::Key | NodeSlot::Merge => {
+                self.skip_depth = 1;
+                return;
+            }
+        };
+        self.record(&path, anchor);
+        self.stack.push(if is_mapping {
+            AnchorFrame::Mapping {
+                path,
+                anchor,
+                key: None,
+                keys: Vec::new(),
+                merges: Vec::new(),
+            }
+        } else {
+            AnchorFrame::Sequence {
+                path,
+                index: 0,
+                merge: false,
+            }
+        });
+    }
+
+    fn end_collection(&mut self) {
+        if self.skip_depth > 0 {
+            self.skip_depth -= 1;
+            // A complex key still occupies the key slot of its mapping
+            if self.skip_depth == 0 {
+                if let Some(AnchorFrame::Mapping { key: None, .. }) = self.stack.last() {
+                    self.set_key(String::new());
+                }
+            }
+            return;
+        }
+        if let Some(AnchorFrame::Mapping {
+            path,
+            anchor,
+            mut keys,
+            merges,
+            ..
+        }) = self.stack.pop()
+        {
+            for merge in merges {
+                let merged_keys = self.mapping_keys.get(&merge).cloned().unwrap_or_default();
+                for merged_key in merged_keys {
+                    if !keys.contains(&merged_key) {
+                        self.record(&join_yaml_path(&path, &merged_key), merge);
+                        keys.push(merged_key);
+                    }
+                }
+            }
+            if anchor > 0 {
+                self.mapping_keys.insert(anchor, keys);
+            }
+        }
+    }
+}
+
+impl yaml_rust2::parser::EventReceiver for YamlAnchorCollector {
+    fn on_event(&mut self, event: yaml_rust2::Event) {
+        use yaml_rust2::Event;
+
+        match event {
+            Event::DocumentStart => {
+                self.current.clear();
+                self.stack.clear();
+                self.skip_depth = 0;
+                self.is_null_document = false;
+            }
+            Event::DocumentEnd => {
+                let sources = std::mem::take(&mut self.current);
+                self.documents.push((sources, self.is_null_document));
+            }
+            Event::Scalar(value, style, anchor, _) => {
+                if self.skip_depth > 0 {
+                    return;
+                }
+                if self.stack.is_empty() {
+                    self.is_null_document = style == yaml_rust2::scanner::TScalarStyle::Plain
+                        && matches!(value.as_str(), "" | "~" | "null" | "Null" | "NULL");
+                }
+                match self.next_slot() {
+                    NodeSlot::Key => self.set_key(value),
+                    NodeSlot::Value(path) => self.record(&path, anchor),
+                    NodeSlot::Merge => {}
+                }
+            }
+            Event::Alias(anchor) => {
+                if self.skip_depth > 0 {
+                    return;
+                }
+                match self.next_slot() {
+                    NodeSlot::Key => self.set_key(String::new()),
+                    NodeSlot::Value(path) => self.record(&path, anchor),
+                    NodeSlot::Merge => self.add_merge(anchor),
+                }
+            }
+            Event::MappingStart(anchor, _) => self.start_collection(anchor, true),
+            Event::SequenceStart(anchor, _) => self.start_collection(anchor, false),
+            Event::MappingEnd | Event::SequenceEnd => self.end_collection(),
+            _ => {}
+        }
+    }
+}
+
+/// Build the identity of a document from dotted key paths, e.g.
+/// `["kind", "metadata.namespace", "metadata.name"]` gives `Deployment/default/web`.
+/// Missing keys are skipped; returns None when none of the keys are present.
+pub fn document_identity(document: &Value, key_paths: &[String]) -> Option<String> {
+    let parts: Vec<String> = key_paths
+        .iter()
+        .filter_map(|key_path| {
+            key_path
+                .split('.')
+                .try_fold(document, |current, key| current.get(key))
+                .map(|value| match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+        })
+        .collect();
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+/// Diff two document streams.
+///
+/// Documents are paired by position, or by identity when `identity_keys` is given
+/// (see [`document_identity`]). Paths are prefixed with the document label, e.g.
+/// `[Deployment/default/web].spec.replicas` or `[1].spec.replicas`.
+/// Documents without an identity are paired by position among themselves.
+pub fn diff_documents(
+    docs1: &[Value],
+    docs2: &[Value],
+    identity_keys: Option<&[String]>,
+    config: &DiffConfig,
+) -> Vec<DiffResult> {
+    let mut results = Vec::new();
+
+    let label = |(index, doc): (usize, &Value)| document_label(doc, index, identity_keys);
+    let labels1: Vec<String> = docs1.iter().enumerate().map(label).collect();
+    let labels2: Vec<String> = docs2.iter().enumerate().map(label).collect();
+
+    let mut matched2 = vec![false; docs2.len()];
+    for (i, doc1) in docs1.iter().enumerate() {
+        let partner = (0..docs2.len()).find(|&j| !matched2[j] && labels2[j] == labels1[i]);
+        match partner {
+            Some(j) => {
+                matched2[j] = true;
+                for diff in diff_with_config(doc1, &docs2[j], config) {
+                    results.push(prefix_diff_path(diff, &labels1[i]));
+                }
+            }
+            None => results.push(DiffResult::Removed(
+                format!("[{}]", labels1[i]),
+                doc1.clone(),
+            )),
+        }
+    }
+
+    for (j, doc2) in docs2.iter().enumerate() {
+        if !matched2[j] {
+            results.push(DiffResult::Added(format!("[{}]", labels2[j]), doc2.clone()));
+        }
+    }
+
+    results
+}
+
+/// Label of a document in a stream: its identity when `identity_keys` is given and
+/// present, otherwise its position
+pub fn document_label(document: &Value, index: usize, identity_keys: Option<&[String]>) -> String {
+    identity_keys
+        .and_then(|keys| document_identity(document, keys))
+        .unwrap_or_else(|| index.to_string())
+}
+
+/// Path of `path` inside the document labelled `label`, as reported by [`diff_documents`]
+pub fn document_path(label: &str, path: &str) -> String {
+    if path.is_empty() {
+        format!("[{label}]")
+    } else if path.starts_with('[') {
+        format!("[{label}]{path}")
+    } else {
+        format!("[{label}].{path}")
+    }
+}
+
+fn prefix_diff_path(diff: DiffResult, label: &str) -> DiffResult {
+    map_diff_path(diff, |path| document_path(label, &path))
+}
+
+fn map_diff_path(diff: DiffResult, f: impl FnOnce(String) -> String) -> DiffResult {
+    match diff {
+        DiffResult::Added(path, value) => DiffResult::Added(f(path), value),
+        DiffResult::Removed(path, value) => DiffResult::Removed(f(path), value),
+        DiffResult::Modified(path, v1, v2) => DiffResult::Modified(f(path), v1, v2),
+        DiffResult::TypeChanged(path, v1, v2) => DiffResult::TypeChanged(f(path), v1, v2),
+        DiffResult::KeyOrderChanged(path, v1, v2) => DiffResult::KeyOrderChanged(f(path), v1, v2),
+    }
+}
+
+/// Options for mapping XML documents onto the diff value model
+#[derive(Debug, Clone, Default)]
+pub struct XmlOptions {
+    /// Represent every child element as an array, even when it occurs once
+    pub elements_as_arrays: bool,
+}
+
+/// Parse XML into a tree rooted at the document element.
+///
+/// Attributes become `@name` keys, text becomes `#text` (or the element's value when it
+/// has neither attributes nor child elements), and child elements are keyed by name
+/// in document order; a name that occurs more than once becomes an array.
+/// An element whose text is mixed with child elements, or whose children of one name
+/// are not adjacent, keeps its content in document order instead: `$children` lists
+/// text as strings and each child element as a `{name: value}` object.
+/// Names in a namespace are written `{uri}local`, except for the default namespace
+/// declared on the document element, which is recorded once as its `@xmlns`.
+pub fn parse_xml(content: &str) -> Result<Value> {
+    parse_xml_with_options(content, &XmlOptions::default())
+}
+
+pub fn parse_xml_with_options(content: &str, options: &XmlOptions) -> Result<Value> {
+    use quick_xml::events::Event;
+    use quick_xml::name::{PrefixDeclaration, ResolveResult};
+
+    let mut reader = quick_xml::NsReader::from_str(content);
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root: Option<(String, Value)> = None;
+    let mut default_namespace: Option<String> = None;
+
+    let qualified = |resolved: ResolveResult, local: &[u8], default_namespace: &Option<String>| {
+        let local = String::from_utf8_lossy(local).into_owned();
+        match resolved {
+            ResolveResult::Unbound => Ok(local),
+            ResolveResult::Bound(ns) => {
+                let uri = String::from_utf8_lossy(ns.into_inner()).into_owned();
+                if default_namespace.as_deref() == Some(uri.as_str()) {
+                    Ok(local)
+                } else {
+                    Ok(format!("{{{uri}}}{local}"))
+                }
+            }
+            ResolveResult::Unknown(prefix) => Err(anyhow!(
+                "Unknown XML namespace prefix '{}'",
+                String::from_utf8_lossy(&prefix)
+            )),
+        }
+    };
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            anyhow!(
+                "Failed to parse XML at position {}: {}",
+                reader.buffer_position(),
+                e
+            )
+        })?;
+        match event {
+            Event::Start(ref start) | Event::Empty(ref start) => {
+                if root.is_some() {
+                    return Err(anyhow!("XML document has more than one root element"));
+                }
+
+                let mut element = XmlElement::default();
+                for attribute in start.attributes() {
+                    let attribute = attribute?;
+                    let value = attribute.unescape_value()?.into_owned();
+                    match attribute.key.as_namespace_binding() {
+                        Some(PrefixDeclaration::Default) if stack.is_empty() => {
+                            element
+                                .attributes
+                                .push(("@xmlns".to_string(), value.clone()));
+                            default_namespace = Some(value);
+                        }
+                        // Prefix choices are not significant once names are resolved
+                        Some(_) => {}
+                        None => {
+                            let (resolved, local) = reader.resolve_attribute(attribute.key);
+                            let name = qualified(resolved, local.into_inner(), &default_namespace)?;
+                            element.attributes.push((format!("@{name}"), value));
+                        }
+                    }
+                }
+                let (resolved, local) = reader.resolve_element(start.name());
+                element.name = qualified(resolved, local.into_inner(), &default_namespace)?;
+
+                if matches!(event, Event::Empty(_)) {
+                    let (name, value) = element.finish(options);
+                    match stack.last_mut() {
+                        Some(parent) => parent.content.push(XmlContent::Element(name, value)),
+                        None => root = Some((name, value)),
+                    }
+                } else {
+                    stack.push(element);
+                }
+            }
+            Event::End(_) => {
+                let element = stack
+                    .pop()
+                    .ok_or_else(|| anyhow!("Unexpected closing tag in XML"))?;
+                let (name, value) = element.finish(options);
+                match stack.last_mut() {
+                    Some(parent) => parent.content.push(XmlContent::Element(name, value)),
+                    None => root = Some((name, value)),
+                }
+            }
+            Event::Text(text) => {
+                let text = text.unescape()?;
+                if let Some(element) = stack.last_mut() {
+                    element.push_text(&text);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.push_text(&String::from_utf8_lossy(&data.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(anyhow!(
+            "Unclosed element <{}> in XML",
+            stack[stack.len() - 1].name
+        ));
+    }
+    let (name, value) = root.ok_or_else(|| anyhow!("XML document has no root element"))?;
+    let mut document = serde_json::Map::new();
+    document.insert(name, value);
+    Ok(Value::Object(document))
+}
+
+/// Key of the content of an element in document order, used for mixed content and
+/// interleaved children. XML names cannot start with `$`, so it never clashes.
+const XML_CHILDREN_KEY: &str = "$children";
+
+#[derive(Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    content: Vec<XmlContent>,
+}
+
+enum XmlContent {
+    Text(String),
+    Element(String, Value),
+}
+
+impl XmlElement {
+    fn push_text(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        // Text split by a comment or a CDATA section is still one run of text
+        match self.content.last_mut() {
+            Some(XmlContent::Text(run)) => {
+                run.push(' ');
+                run.push_str(text);
+            }
+            _ => self.content.push(XmlContent::Text(text.to_string())),
+        }
+    }
+
+    /// Whether grouping children by name would lose their order: text between or
+    /// around elements, or a name that comes back after another one
+    fn needs_document_order(&self) -> bool {
+        let mut has_text = false;
+        let mut has_elements = false;
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut previous: Option<&str> = None;
+        for item in &self.content {
+            match item {
+                XmlContent::Text(_) => has_text = true,
+                XmlContent::Element(name, _) => {
+                    has_elements = true;
+                    if previous != Some(name.as_str()) && !seen.insert(name.as_str()) {
+                        return true;
+                    }
+                    previous = Some(name.as_str());
+                }
+            }
+        }
+        has_text && has_elements
+    }
+
+    fn finish(self, options: &XmlOptions) -> (String, Value) {
+        let has_elements = self
+            .content
+            .iter()
+            .any(|item| matches!(item, XmlContent::Element(..)));
+        if self.attributes.is_empty() && !has_elements {
+            let text = match self.content.into_iter().next() {
+                Some(XmlContent::Text(text)) => text,
+                _ => String::new(),
+            };
+            return (self.name, Value::String(text));
+        }
+
+        let ordered = self.needs_document_order();
+        let mut map = serde_json::Map::new();
+        for (name, value) in self.attributes {
+            map.insert(name, Value::String(value));
+        }
+
+        if ordered {
+            let children = self
+                .content
+                .into_iter()
+                .map(|item| match item {
+                    XmlContent::Text(text) => Value::String(text),
+                    XmlContent::Element(name, value) => {
+                        let mut child = serde_json::Map::new();
+                        child.insert(name, value);
+                        Value::Object(child)
+                    }
+                })
+                .collect();
+            map.insert(XML_CHILDREN_KEY.to_string(), Value::Array(children));
+            return (self.name, Value::Object(map));
+        }
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for item in &self.content {
+            if let XmlContent::Element(name, _) = item {
+                *counts.entry(name.as_str()).or_default() += 1;
+            }
+        }
+        let as_array: Vec<bool> = self
+            .content
+            .iter()
+            .map(|item| match item {
+                XmlContent::Element(name, _) => {
+                    options.elements_as_arrays || counts[name.as_str()] > 1
+                }
+                XmlContent::Text(_) => false,
+            })
+            .collect();
+
+        for (item, as_array) in self.content.into_iter().zip(as_array) {
+            match item {
+                // Without child elements, text is the only content
+                XmlContent::Text(text) => {
+                    map.insert("#text".to_string(), Value::String(text));
+                }
+                XmlContent::Element(name, value) if as_array => {
+                    let slot = map.entry(name).or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(items) = slot {
+                        items.push(value);
+                    }
+                }
+                XmlContent::Element(name, value) => {
+                    map.insert(name, value);
+                }
+            }
+        }
+
+        (self.name, Value::Object(map))
+    }
+}
+
+/// Make a child element that occurs once on one side and several times on the other
+/// an array on both sides, so that going from one `<book>` to two is reported as an
+/// added element rather than a type change
+pub fn align_xml_repeated_elements(old: &mut Value, new: &mut Value) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old.iter_mut() {
+                let Some(new_value) = new.get_mut(key) else {
+                    continue;
+                };
+                if !key.starts_with(['@', '#', '$']) {
+                    match (old_value.is_array(), new_value.is_array()) {
+                        (true, false) => *new_value = Value::Array(vec![new_value.take()]),
+                        (false, true) => *old_value = Value::Array(vec![old_value.take()]),
+                        _ => {}
+                    }
+                }
+                align_xml_repeated_elements(old_value, new_value);
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for (old_item, new_item) in old.iter_mut().zip(new.iter_mut()) {
+                align_xml_repeated_elements(old_item, new_item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rewrite a diff path over a parsed XML document as an XPath-like location, e.g.
+/// `catalog.book[1].@id` becomes `/catalog/book[2]/@id` and `#text` becomes `text()`.
+/// Positions are 1-based as in XPath; `[key=value]` selectors are kept as they are.
+/// Entries of `$children` become `text()[n]` or `name[n]`, counted among the
+/// siblings of the same kind.
+pub fn xml_path(document: &Value, path: &str) -> String {
+    let mut xpath = String::new();
+    let mut current = Some(document);
+    let mut rest = path;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            // A key with dots or brackets, as in `data["config.json"]`
+            let mut quoted = serde_json::Deserializer::from_str(after).into_iter::<String>();
+            if let Some(Ok(key)) = quoted.next() {
+                if let Some(after_key) = after[quoted.byte_offset()..].strip_prefix(']') {
+                    xpath.push('/');
+                    xpath.push_str(&key);
+                    current = current.and_then(|value| value.get(&key));
+                    rest = after_key;
+                    continue;
+                }
+            }
+            let end = after.find(']').unwrap_or(after.len());
+            let selector = &after[..end];
+            rest = after.get(end + 1..).unwrap_or("");
+            if let Ok(index) = selector.parse::<usize>() {
+                xpath.push_str(&format!("[{}]", index + 1));
+                current = current.and_then(|value| value.get(index));
+            } else {
+                xpath.push_str(&format!("[{selector}]"));
+                current = current.and_then(|value| find_by_selector(value, selector));
+            }
+            continue;
+        }
+
+        let remaining = rest.strip_prefix('.').unwrap_or(rest);
+        // Element names may contain dots, so prefer the longest key of the current node
+        let key = current
+            .and_then(Value::as_object)
+            .and_then(|map| {
+                map.keys()
+                    .filter(|key| {
+                        remaining.starts_with(key.as_str())
+                            && matches!(
+                                remaining[key.len()..].chars().next(),
+                                None | Some('.' | '[')
+                            )
+                    })
+                    .max_by_key(|key| key.len())
+            })
+            .map(String::as_str)
+            .unwrap_or_else(|| &remaining[..remaining.find(['.', '[']).unwrap_or(remaining.len())]);
+        if key.is_empty() {
+            break;
+        }
+        rest = &remaining[key.len()..];
+
+        if key == XML_CHILDREN_KEY {
+            let children = current
+                .and_then(|value| value.get(key))
+                .and_then(Value::as_array);
+            let entry = rest.strip_prefix('[').and_then(|after| {
+                let (index, after) = after.split_once(']')?;
+                let index = index.parse::<usize>().ok()?;
+                Some((children?.get(..=index)?.split_last()?, after))
+            });
+            let Some(((entry, preceding), after)) = entry else {
+                xpath.push_str("/node()");
+                current = None;
+                continue;
+            };
+            match entry.as_object().and_then(|child| child.iter().next()) {
+                Some((name, value)) => {
+                    let position = preceding
+                        .iter()
+                        .filter(|item| item.get(name).is_some())
+                        .count();
+                    xpath.push_str(&format!("/{name}[{}]", position + 1));
+                    current = Some(value);
+                    // The child's own key follows the entry, as in `[1].b` or `[1]["a.b"]`
+                    let segment = join_yaml_path("_", name);
+                    rest = after.strip_prefix(&segment[1..]).unwrap_or(after);
+                }
+                None => {
+                    let position = preceding.iter().filter(|item| item.is_string()).count();
+                    xpath.push_str(&format!("/text()[{}]", position + 1));
+                    current = Some(entry);
+                    rest = after;
+                }
+            }
+            continue;
+        }
+
+        xpath.push('/');
+        xpath.push_str(if key == "#text" { "text()" } else { key });
+        current = current.and_then(|value| value.get(key));
+    }
+
+    if xpath.is_empty() {
+        "/".to_string()
+    } else {
+        xpath
+    }
+}
+
+fn find_by_selector<'a>(value: &'a Value, selector: &str) -> Option<&'a Value> {
+    let (key, id) = selector.split_once('=')?;
+    let id: Value = serde_json::from_str(id).ok()?;
+    value
+        .as_array()?
+        .iter()
+        .find(|item| item.get(key) == Some(&id))
+}
+
+/// Rewrite the paths of differences between two XML documents with [`xml_path`]
+pub fn xml_diff_paths(differences: Vec<DiffResult>, old: &Value, new: &Value) -> Vec<DiffResult> {
+    differences
+        .into_iter()
+        .map(|diff| {
+            let document = if matches!(diff, DiffResult::Added(..)) {
+                new
+            } else {
+                old
+            };
+            map_diff_path(diff, |path| xml_path(document, &path))
+        })
+        .collect()
+}
+
+/// Options for reading CSV and other delimiter-separated files
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Field delimiter, e.g. `b'\t'` for TSV
+    pub delimiter: u8,
+    /// Quote character, or None to read quote characters as ordinary text
+    pub quote: Option<u8>,
+    /// Read the first row as column names; without headers each row is an array of fields
+    pub has_headers: bool,
+    /// Columns identifying a row, checked to exist and to be unique. Rows are matched
+    /// by them when they are also given as [`DiffConfig::array_id_key`]. Without
+    /// headers, key columns are 0-based field indexes.
+    pub key_columns: Vec<String>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: Some(b'"'),
+            has_headers: true,
+            key_columns: Vec::new(),
+        }
+    }
+}
+
+pub fn parse_csv(content: &str) -> Result<Value> {
+    parse_csv_with_options(content, &CsvOptions::default())
+}
+
+pub fn parse_csv_with_options(content: &str, options: &CsvOptions) -> Result<Value> {
+    let mut builder = ReaderBuilder::new();
+    builder
+        .delimiter(options.delimiter)
+        .has_headers(options.has_headers);
+    match options.quote {
+        Some(quote) => builder.quote(quote),
+        None => builder.quoting(false),
+    };
+    let mut reader = builder.from_reader(content.as_bytes());
+
+    let headers = if options.has_headers {
+        Some(reader.headers()?.clone()).filter(|headers| !headers.is_empty())
+    } else {
+        None
+    };
+
+    for column in &options.key_columns {
+        let found = match &headers {
+            Some(headers) => headers.iter().any(|header| header == column),
+            None => column.parse::<usize>().is_ok(),
+        };
+        if !found {
+            return Err(anyhow!("Key column '{}' not found in CSV header", column));
+        }
+    }
+
+    let mut records = Vec::new();
+
+    for result in reader.into_records() {
+        let record = result?;
+        let row = if let Some(headers) = &headers {
+            let mut obj = serde_json::Map::new();
+            for (i, header) in headers.iter().enumerate() {
+                if let Some(value) = record.get(i) {
+                    obj.insert(
+                        escape_reserved_key(header.to_string()),
+                        Value::String(value.to_string()),
+                    );
+                }
+            }
+            Value::Object(obj)
+        } else {
+            let mut arr = Vec::new();
+            for field in record.iter() {
+                arr.push(Value::String(field.to_string()));
+            }
+            Value::Array(arr)
+        };
+
+        records.push(row);
+    }
+
+    if let Some(key) = duplicate_row_key(&records, &options.key_columns) {
+        return Err(anyhow!("Duplicate CSV row key '{}'", key));
+    }
+    Ok(Value::Array(records))
+}
+
+/// The first row key that several rows share, e.g. `[id="42"]`. Rows are matched by
+/// their key columns through [`DiffConfig::array_id_key`], which needs them unique.
+fn duplicate_row_key(rows: &[Value], key_columns: &[String]) -> Option<String> {
+    if key_columns.is_empty() {
+        return None;
+    }
+    let id_keys: Vec<&str> = key_columns.iter().map(String::as_str).collect();
+    let mut seen = HashSet::new();
+    rows.iter()
+        .filter_map(|row| element_id(row, &id_keys))
+        .find(|id_val| !seen.insert(id_val.clone()))
+        .map(|id_val| element_id_path("", &id_keys, &id_val))
+}
+
+/// Options for reading columnar data files (Parquet, Arrow IPC)
+#[derive(Debug, Clone)]
+pub struct ColumnarOptions {
+    /// Columns identifying a row, checked like [`CsvOptions::key_columns`]
+    pub key_columns: Vec<String>,
+    /// Rows decoded per record batch
+    pub batch_size: usize,
+    /// Show binary columns as hex instead of base64
+    pub bytes_as_hex: bool,
+}
+
+impl Default for ColumnarOptions {
+    fn default() -> Self {
+        Self {
+            key_columns: Vec::new(),
+            batch_size: 8192,
+            bytes_as_hex: false,
+        }
+    }
+}
+
+/// Parse a Parquet file into its schema and rows.
+///
+/// The result has a `schema` object mapping each column to its Arrow data type, so
+/// that added, removed and retyped columns are reported apart from the data, and a
+/// `rows` array. The reader is a [`std::fs::File`] or, for data already
+/// in memory, [`bytes::Bytes`]. Every row is held in the result; [`read_parquet`] and
+/// [`diff_columnar`] compare large files without loading them.
+pub fn parse_parquet<R>(reader: R, options: &ColumnarOptions) -> Result<Value>
+where
+    R: parquet::file::reader::ChunkReader + 'static,
+{
+    read_parquet(reader, options)?.into_value()
+}
+
+/// Parse an Arrow IPC file (`.arrow`, Feather v2) or stream into its schema and rows,
+/// in the same shape as [`parse_parquet`]
+pub fn parse_arrow_ipc<R: Read + Seek>(reader: R, options: &ColumnarOptions) -> Result<Value> {
+    read_arrow_ipc(reader, options)?.into_value()
+}
+
+/// Open a Parquet file for reading its rows one record batch at a time
+pub fn read_parquet<R>(reader: R, options: &ColumnarOptions) -> Result<ColumnarRows<'static>>
+where
+    R: parquet::file::reader::ChunkReader + 'static,
+{
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(reader)
+        .map_err(|e| anyhow!("Failed to parse Parquet: {}", e))?;
+    let schema = builder.schema().clone();
+    let reader = builder
+        .with_batch_size(options.batch_size)
+        .build()
+        .map_err(|e| anyhow!("Failed to parse Parquet: {}", e))?;
+    ColumnarRows::new("Parquet", &schema, reader, options)
+}
+
+/// Open an Arrow IPC file or stream for reading its rows one record batch at a time
+pub fn read_arrow_ipc<'a, R: Read + Seek + 'a>(
+    mut reader: R,
+    options: &ColumnarOptions,
+) -> Result<ColumnarRows<'a>> {
+    use arrow_ipc::reader::{FileReader, StreamReader};
+
+    let mut magic = [0u8; 6];
+    let is_file = reader.read_exact(&mut magic).is_ok() && &magic == b"ARROW1";
+    reader.rewind()?;
+    let error = |e: arrow_schema::ArrowError| anyhow!("Failed to parse Arrow IPC: {}", e);
+    if is_file {
+        let reader = FileReader::try_new(reader, None).map_err(error)?;
+        let schema = reader.schema();
+        ColumnarRows::new("Arrow IPC", &schema, reader, options)
+    } else {
+        let reader = StreamReader::try_new(std::io::BufReader::new(reader), None).map_err(error)?;
+        let schema = reader.schema();
+        ColumnarRows::new("Arrow IPC", &schema, reader, options)
+    }
+}
+
+/// The schema and rows of a Parquet or Arrow IPC file. Rows are decoded as they are
+/// iterated, so only one record batch of them is held at a time.
+pub struct ColumnarRows<'a> {
+    schema: serde_json::Map<String, Value>,
+    key_columns: Vec<String>,
+    rows: Box<dyn Iterator<Item = Result<Value>> + 'a>,
+}
+
+impl<'a> ColumnarRows<'a> {
+    fn new<I>(
+        format: &'static str,
+        schema: &arrow_schema::Schema,
+        mut batches: I,
+        options: &ColumnarOptions,
+    ) -> Result<Self>
+    where
+        I: Iterator<Item = std::result::Result<arrow_array::RecordBatch, arrow_schema::ArrowError>>
+            + 'a,
+    {
+        let names: Vec<String> = schema
+            .fields()
+            .iter()
+            .map(|field| escape_reserved_key(field.name().clone()))
+            .collect();
+        let schema_value = names
+            .iter()
+            .zip(schema.fields())
+            .map(|(name, field)| (name.clone(), Value::String(field.data_type().to_string())))
+            .collect();
+        for column in &options.key_columns {
+            if schema.index_of(column).is_err() {
+                return Err(anyhow!(
+                    "Failed to parse {format}: Schema error: Key column '{column}' not found in schema"
+                ));
+            }
+        }
+
+        let key_columns = options.key_columns.clone();
+        let options = options.clone();
+        let mut decoded = Vec::new().into_iter();
+        let rows = std::iter::from_fn(move || loop {
+            if let Some(row) = decoded.next() {
+                return Some(Ok(row));
+            }
+            match batches
+                .next()?
+                .and_then(|batch| batch_rows(&names, &batch, &options))
+            {
+                Ok(rows) => decoded = rows.into_iter(),
+                Err(e) => return Some(Err(anyhow!("Failed to parse {format}: {}", e))),
+            }
+        });
+
+        Ok(Self {
+            schema: schema_value,
+            key_columns,
+            rows: Box::new(rows),
+        })
+    }
+
+    /// Column names and their Arrow data types
+    pub fn schema(&self) -> &serde_json::Map<String, Value> {
+        &self.schema
+    }
+
+    /// Read every row into a `schema` and `rows` object, as [`parse_parquet`] returns
+    pub fn into_value(self) -> Result<Value> {
+        let key_columns = self.key_columns;
+        let schema = self.schema;
+        let records = self.rows.collect::<Result<Vec<Value>>>()?;
+        if let Some(key) = duplicate_row_key(&records, &key_columns) {
+            return Err(anyhow!("Duplicate row key '{key}'"));
+        }
+
+        let mut result = serde_json::Map::new();
+        result.insert("schema".to_string(), Value::Object(schema));
+        result.insert("rows".to_string(), Value::Array(records));
+        Ok(Value::Object(result))
+    }
+}
+
+impl Iterator for ColumnarRows<'_> {
+    type Item = Result<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next()
+    }
+}
+
+/// Convert the rows of one record batch into objects keyed by column name
+fn batch_rows(
+    names: &[String],
+    batch: &arrow_array::RecordBatch,
+    options: &ColumnarOptions,
+) -> std::result::Result<Vec<Value>, arrow_schema::ArrowError> {
+    // Dictionary-encoded columns are decoded once per batch rather than per cell
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|column| match column.data_type() {
+            arrow_schema::DataType::Dictionary(_, value_type) => {
+                arrow_cast::cast(column, value_type)
+            }
+            _ => Ok(column.clone()),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    (0..batch.num_rows())
+        .map(|row| {
+            let values = columns
+                .iter()
+                .map(|column| arrow_value(column.as_ref(), row, options))
+                .collect::<std::result::Result<Vec<Value>, _>>()?;
+            Ok(Value::Object(names.iter().cloned().zip(values).collect()))
+        })
+        .collect()
+}
+
+/// Compare two columnar files as [`diff_with_config`] compares their parsed values,
+/// reading the rows of both side by side instead of loading them.
+///
+/// Rows are paired by position, or by the keys [`DiffConfig::array_id_key`] gives for
+/// `rows`, and a key must then be unique. Only rows whose match has not been read yet
+/// are held, so files in much the same row order are compared in about one record
+/// batch of memory each. Decoding embedded strings needs whole values, so with
+/// [`DiffConfig::decode_embedded`] or [`DiffConfig::decode_base64`] both files are
+/// loaded.
+pub fn diff_columnar(
+    old: ColumnarRows,
+    new: ColumnarRows,
+    config: &DiffConfig,
+) -> Result<Vec<DiffResult>> {
+    if config.decode_embedded.is_some() || config.decode_base64.is_some() {
+        return Ok(diff_with_config(
+            &old.into_value()?,
+            &new.into_value()?,
+            config,
+        ));
+    }
+
+    let schema_only = |schema: &serde_json::Map<String, Value>| {
+        let mut value = serde_json::Map::new();
+        value.insert("schema".to_string(), Value::Object(schema.clone()));
+        Value::Object(value)
+    };
+    let mut results =
+        diff_with_config(&schema_only(&old.schema), &schema_only(&new.schema), config);
+    if let Some(regex) = &config.ignore_keys_regex {
+        if regex.is_match("rows") {
+            return Ok(results);
+        }
+    }
+
+    match ArrayIdKeys::from_config(config).at("rows") {
+        Some(id_keys) => diff_keyed_rows(old, new, &id_keys, config, &mut results)?,
+        None => {
+            let (mut old, mut new) = (old.fuse(), new.fuse());
+            for index in 0.. {
+                let path = format!("rows[{index}]");
+                match (old.next().transpose()?, new.next().transpose()?) {
+                    (Some(row1), Some(row2)) => diff_row(path, &row1, &row2, config, &mut results),
+                    (Some(row1), None) => results.push(DiffResult::Removed(path, row1)),
+                    (None, Some(row2)) => results.push(DiffResult::Added(path, row2)),
+                    (None, None) => break,
+                }
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Pair rows by key as they are read from both files, reporting them in the order
+/// `diff_arrays` does: changed and removed rows in old order, then added rows
+fn diff_keyed_rows(
+    old: ColumnarRows,
+    new: ColumnarRows,
+    id_keys: &[&str],
+    config: &DiffConfig,
+    results: &mut Vec<DiffResult>,
+) -> Result<()> {
+    type Pending = HashMap<Vec<Value>, (usize, Value)>;
+    let owned_id = |row: &Value| -> Option<Vec<Value>> {
+        Some(element_id(row, id_keys)?.into_iter().cloned().collect())
+    };
+    let id_path = |id: &[Value]| element_id_path("rows", id_keys, &id.iter().collect::<Vec<_>>());
+
+    let mut pending: [Pending; 2] = Default::default();
+    let mut seen: [HashSet<Vec<Value>>; 2] = Default::default();
+    let mut unkeyed: [Vec<(usize, Value)>; 2] = Default::default();
+    // (old or new order, position) of each difference, sorted once both files are read
+    let mut keyed: Vec<((bool, usize), DiffResult)> = Vec::new();
+
+    let mut sides = [old.fuse(), new.fuse()];
+    let mut indexes = [0usize; 2];
+    loop {
+        let mut read_any = false;
+        for side in 0..2 {
+            let Some(row) = sides[side].next().transpose()? else {
+                continue;
+            };
+            read_any = true;
+            let index = indexes[side];
+            indexes[side] += 1;
+
+            let Some(id) = owned_id(&row) else {
+                unkeyed[side].push((index, row));
+                continue;
+            };
+            if !seen[side].insert(id.clone()) {
+                return Err(anyhow!("Duplicate row key '{}'", id_path(&id)));
+            }
+            match pending[1 - side].remove(&id) {
+                Some((other_index, other_row)) => {
+                    let (old_index, old_row, new_row) = if side == 0 {
+                        (index, row, other_row)
+                    } else {
+                        (other_index, other_row, row)
+                    };
+                    let mut row_results = Vec::new();
+                    diff_row(id_path(&id), &old_row, &new_row, config, &mut row_results);
+                    keyed.extend(
+                        row_results
+                            .into_iter()
+                            .map(|diff| ((false, old_index), diff)),
+                    );
+                }
+                None => {
+                    pending[side].insert(id, (index, row));
+                }
+            }
+        }
+        if !read_any {
+            break;
+        }
+    }
+
+    let [removed, added] = pending;
+    for (id, (index, row)) in removed {
+        keyed.push(((false, index), DiffResult::Removed(id_path(&id), row)));
+    }
+    for (id, (index, row)) in added {
+        keyed.push(((true, index), DiffResult::Added(id_path(&id), row)));
+    }
+    keyed.sort_by_key(|(order, _)| *order);
+    results.extend(keyed.into_iter().map(|(_, diff)| diff));
+
+    // Rows without the key columns are compared by position, as in `diff_arrays`
+    let [unkeyed1, unkeyed2] = unkeyed;
+    let mut unkeyed2 = unkeyed2.into_iter();
+    for (index1, row1) in unkeyed1 {
+        match unkeyed2.next() {
+            Some((_, row2)) => diff_row(format!("rows[{index1}]"), &row1, &row2, config, results),
+            None => results.push(DiffResult::Removed(format!("rows[{index1}]"), row1)),
+        }
+    }
+    for (index2, row2) in unkeyed2 {
+        results.push(DiffResult::Added(format!("rows[{index2}]"), row2));
+    }
+    Ok(())
+}
+
+/// Compare two rows paired by [`diff_columnar`]
+fn diff_row(
+    path: String,
+    row1: &Value,
+    row2: &Value,
+    config: &DiffConfig,
+    results: &mut Vec<DiffResult>,
+) {
+    if is_nested_pair(row1, row2) {
+        diff_recursive(
+            &path,
+            row1,
+            row2,
+            results,
+            config.ignore_keys_regex.as_ref(),
+            config.epsilon,
+            ArrayIdKeys::from_config(config),
+            config.ignore_whitespace,
+            config.ignore_case,
+            config.check_key_order,
+        );
+    } else if !values_are_equal_with_config(
+        row1,
+        row2,
+        config.epsilon,
+        config.ignore_whitespace,
+        config.ignore_case,
+    ) {
+        if value_type_name(row1) == value_type_name(row2) {
+            results.push(DiffResult::Modified(path, row1.clone(), row2.clone()));
+        } else {
+            results.push(DiffResult::TypeChanged(path, row1.clone(), row2.clone()));
+        }
+    }
+}
+
+/// Convert one cell of an Arrow array. Dates and times become date/times, decimals
+/// exact numbers, binary data bytes values, and lists, structs and maps nested values.
+fn arrow_value(
+    array: &dyn arrow_array::Array,
+    row: usize,
+    options: &ColumnarOptions,
+) -> std::result::Result<Value, arrow_schema::ArrowError> {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::*;
+    use arrow_array::Array;
+    use arrow_schema::DataType;
+
+    if array.is_null(row) {
+        return Ok(Value::Null);
+    }
+    let formatted = || -> std::result::Result<String, arrow_schema::ArrowError> {
+        let formatter = arrow_cast::display::ArrayFormatter::try_new(
+            array,
+            &arrow_cast::display::FormatOptions::default(),
+        )?;
+        Ok(formatter.value(row).to_string())
+    };
+    let hex = options.bytes_as_hex;
+    let nested = |values: &dyn Array| {
+        (0..values.len())
+            .map(|i| arrow_value(values, i, options))
+            .collect::<std::result::Result<Vec<Value>, _>>()
+            .map(Value::Array)
+    };
+
+    Ok(match array.data_type() {
+        DataType::Null => Value::Null,
+        DataType::Boolean => Value::Bool(array.as_boolean().value(row)),
+        DataType::Int8 => Value::from(array.as_primitive::<Int8Type>().value(row)),
+        DataType::Int16 => Value::from(array.as_primitive::<Int16Type>().value(row)),
+        DataType::Int32 => Value::from(array.as_primitive::<Int32Type>().value(row)),
+        DataType::Int64 => Value::from(array.as_primitive::<Int64Type>().value(row)),
+        DataType::UInt8 => Value::from(array.as_primitive::<UInt8Type>().value(row)),
+        DataType::UInt16 => Value::from(array.as_primitive::<UInt16Type>().value(row)),
+        DataType::UInt32 => Value::from(array.as_primitive::<UInt32Type>().value(row)),
+        DataType::UInt64 => Value::from(array.as_primitive::<UInt64Type>().value(row)),
+        // Through the shortest decimal spelling, so that 0.1f stays 0.1
+        DataType::Float16 | DataType::Float32 => {
+            float_value(formatted()?.parse().unwrap_or(f64::NAN))
+        }
+        DataType::Float64 => float_value(array.as_primitive::<Float64Type>().value(row)),
+        DataType::Decimal128(..) | DataType::Decimal256(..) => {
+            let decimal = formatted()?;
+            decimal
+                .parse::<serde_json::Number>()
+                .map(Value::Number)
+                .unwrap_or(Value::String(decimal))
+        }
+        DataType::Utf8 => Value::from(array.as_string::<i32>().value(row)),
+        DataType::LargeUtf8 => Value::from(array.as_string::<i64>().value(row)),
+        DataType::Utf8View => Value::from(array.as_string_view().value(row)),
+        DataType::Binary => bytes_value(array.as_binary::<i32>().value(row), hex),
+        DataType::LargeBinary => bytes_value(array.as_binary::<i64>().value(row), hex),
+        DataType::BinaryView => bytes_value(array.as_binary_view().value(row), hex),
+        DataType::FixedSizeBinary(_) => bytes_value(array.as_fixed_size_binary().value(row), hex),
+        DataType::Date32
+        | DataType::Date64
+        | DataType::Timestamp(..)
+        | DataType::Time32(_)
+        | DataType::Time64(_) => datetime_value(formatted()?),
+        DataType::List(_) => nested(array.as_list::<i32>().value(row).as_ref())?,
+        DataType::LargeList(_) => nested(array.as_list::<i64>().value(row).as_ref())?,
+        DataType::FixedSizeList(..) => nested(array.as_fixed_size_list().value(row).as_ref())?,
+        DataType::Struct(fields) => {
+            let columns = array.as_struct().columns();
+            let mut map = serde_json::Map::new();
+            for (field, column) in fields.iter().zip(columns) {
+                map.insert(
+                    escape_reserved_key(field.name().clone()),
+                    arrow_value(column.as_ref(), row, options)?,
+                );
+            }
+            Value::Object(map)
+        }
+        DataType::Map(..) => {
+            let entries = array.as_map().value(row);
+            let mut map = serde_json::Map::new();
+            for i in 0..entries.len() {
+                let key = match arrow_value(entries.column(0).as_ref(), i, options)? {
+                    Value::String(key) => key,
+                    other => format_value(&other),
+                };
+                let key = escape_reserved_key(key);
+                map.insert(key, arrow_value(entries.column(1).as_ref(), i, options)?);
+            }
+            Value::Object(map)
+        }
+        DataType::Dictionary(_, value_type) => {
+            let cell = arrow_cast::cast(&array.slice(row, 1), value_type)?;
+            arrow_value(cell.as_ref(), 0, options)?
+        }
+        _ => Value::String(formatted()?),
+    })
+}
+
+/// Options for reading spreadsheets (XLSX, XLS, ODS)
+#[derive(Debug, Clone, Default)]
+pub struct SpreadsheetOptions {
+    /// How sheets are read as tables, as for CSV: whether the first row holds column
+    /// names, and the key columns identifying rows. Without key columns every cell is
+    /// compared by its coordinates, as `Sheet1!C17`. The delimiter and quote character
+    /// do not apply.
+    pub table: CsvOptions,
+    /// Compare the text of formulas (`=SUM(B2:B9)`) instead of their cached values
+    pub compare_formulas: bool,
+}
+
+/// Parse a spreadsheet workbook (`.xlsx`, `.xlsm`, `.xls`, `.ods`).
+///
+/// Without key columns the result has one `Sheet1!C17` entry per non-empty cell.
+/// With key columns each sheet is read as a table like [`parse_csv_with_options`]:
+/// the header row names the columns and every other row becomes a record in the
+/// sheet's array, matched as `Sheet1[sku="SKU-42"]` by [`DiffConfig::array_id_key`].
+/// Cells hold the values cached by the application
+/// that saved the file, or `=` and the formula text with `compare_formulas`.
+pub fn parse_spreadsheet(content: &[u8], options: &SpreadsheetOptions) -> Result<Value> {
+    use calamine::Reader;
+
+    let mut workbook = calamine::open_workbook_auto_from_rs(std::io::Cursor::new(content))
+        .map_err(|e| anyhow!("Failed to parse spreadsheet: {}", e))?;
+
+    let mut result = serde_json::Map::new();
+    for sheet in workbook.sheet_names() {
+        let range = workbook
+            .worksheet_range(&sheet)
+            .map_err(|e| anyhow!("Failed to read sheet '{}': {}", sheet, e))?;
+        let formulas = if options.compare_formulas {
+            Some(
+                workbook
+                    .worksheet_formula(&sheet)
+                    .map_err(|e| anyhow!("Failed to read formulas of sheet '{}': {}", sheet, e))?,
+            )
+        } else {
+            None
+        };
+
+        // Cells by absolute (row, column), with formulas taking the place of values
+        let mut cells: BTreeMap<(u32, u32), Value> = BTreeMap::new();
+        if let Some((start_row, start_col)) = range.start() {
+            for (row, col, data) in range.used_cells() {
+                let position = (start_row + row as u32, start_col + col as u32);
+                cells.insert(position, spreadsheet_cell_value(data));
+            }
+        }
+        if let Some((formulas, (start_row, start_col))) =
+            formulas.as_ref().and_then(|f| Some((f, f.start()?)))
+        {
+            for (row, col, formula) in formulas.used_cells() {
+                let position = (start_row + row as u32, start_col + col as u32);
+                cells.insert(position, Value::String(format!("={formula}")));
+            }
+        }
+
+        if options.table.key_columns.is_empty() {
+            for ((row, col), value) in cells {
+                let key = format!("{sheet}!{}", cell_reference(row, col));
+                result.insert(escape_reserved_key(key), value);
+            }
+        } else {
+            let rows = spreadsheet_table(&sheet, cells, options)?;
+            result.insert(escape_reserved_key(sheet), Value::Array(rows));
+        }
+    }
+    Ok(Value::Object(result))
+}
+
+/// Read the cells of one sheet as rows with unique key columns
+fn spreadsheet_table(
+    sheet: &str,
+    cells: BTreeMap<(u32, u32), Value>,
+    options: &SpreadsheetOptions,
+) -> Result<Vec<Value>> {
+    let mut rows: BTreeMap<u32, BTreeMap<u32, Value>> = BTreeMap::new();
+    for ((row, col), value) in cells {
+        rows.entry(row).or_default().insert(col, value);
+    }
+    let mut rows = rows.into_values();
+
+    // Column names from the header row, or 0-based indexes without one
+    let headers: BTreeMap<u32, String> = if options.table.has_headers {
+        rows.next()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(col, name)| match name {
+                Value::String(name) => (col, name),
+                other => (col, format_value(&other)),
+            })
+            .collect()
+    } else {
+        BTreeMap::new()
+    };
+    for key in &options.table.key_columns {
+        let found = if options.table.has_headers {
+            headers.values().any(|name| name == key)
+        } else {
+            key.parse::<u32>().is_ok()
+        };
+        if !found {
+            return Err(anyhow!(
+                "Key column '{}' not found in sheet '{}'",
+                key,
+                sheet
+            ));
+        }
+    }
+
+    let mut records = Vec::new();
+    for row in rows {
+        let record = row
+            .into_iter()
+            .map(|(col, value)| {
+                let name = match headers.get(&col) {
+                    Some(name) => name.clone(),
+                    // A cell beyond the named columns is named by its column letter
+                    None if options.table.has_headers => column_letters(col),
+                    None => col.to_string(),
+                };
+                (escape_reserved_key(name), value)
+            })
+            .collect();
+        records.push(Value::Object(record));
+    }
+    if let Some(key) = duplicate_row_key(&records, &options.table.key_columns) {
+        return Err(anyhow!("Duplicate spreadsheet row key '{}{}'", sheet, key));
+    }
+    Ok(records)
+}
+
+fn spreadsheet_cell_value(data: &calamine::Data) -> Value {
+    use calamine::Data;
+
+    match data {
+        Data::Int(i) => Value::from(*i),
+        Data::Float(f) => float_value(*f),
+        Data::String(s) => Value::String(s.clone()),
+        Data::Bool(b) => Value::Bool(*b),
+        Data::DateTime(datetime) if datetime.is_datetime() => {
+            let (year, month, day, hour, minute, second, milli) = datetime.to_ymd_hms_milli();
+            let date = format!("{year:04}-{month:02}-{day:02}");
+            if (hour, minute, second, milli) == (0, 0, 0, 0) {
+                datetime_value(date)
+            } else if milli == 0 {
+                datetime_value(format!("{date}T{hour:02}:{minute:02}:{second:02}"))
+            } else {
+                datetime_value(format!(
+                    "{date}T{hour:02}:{minute:02}:{second:02}.{milli:03}"
+                ))
+            }
+        }
+        // Durations are kept as Excel stores them, in days
+        Data::DateTime(duration) => float_value(duration.as_f64()),
+        Data::DateTimeIso(datetime) => datetime_value(datetime.clone()),
+        Data::DurationIso(duration) => Value::String(duration.clone()),
+        Data::Error(error) => Value::String(error.to_string()),
+        Data::Empty => Value::Null,
+    }
+}
+
+/// A1-style reference of a 0-based cell position, e.g. (16, 2) is `C17`
+fn cell_reference(row: u32, col: u32) -> String {
+    format!("{}{}", column_letters(col), row + 1)
+}
+
+/// Letters of a 0-based column index: `A`, ..., `Z`, `AA`, ...
+fn column_letters(col: u32) -> String {
+    let mut letters = Vec::new();
+    let mut col = col + 1;
+    while col > 0 {
+        letters.push(char::from(b'A' + ((col - 1) % 26) as u8));
+        col = (col - 1) / 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Read a SQLite database into its schema and the rows of every table.
+///
+/// `schema.tables` maps each table to its columns with their declared type and
+/// constraints, and to the `row_key` its rows are identified by: the primary key, or
+/// `rowid` for tables without one, which is then read into every row as well.
+/// `schema.indexes`, `schema.views` and `schema.triggers` hold the SQL that created
+/// them, with whitespace collapsed. `tables` maps each table to its rows; with the
+/// keys from [`sqlite_row_keys`] as [`DiffConfig::array_id_keys`], one cell is named
+/// `tables.users[id=7].email`. The database is opened read-only and BLOB values
+/// become bytes values.
+pub fn parse_sqlite(path: &Path, options: &BinaryOptions) -> Result<Value> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| anyhow!("Failed to open SQLite database: {}", e))?;
+    sqlite_to_json(&conn, options).map_err(|e| anyhow!("Failed to read SQLite database: {}", e))
+}
+
+fn sqlite_to_json(conn: &rusqlite::Connection, options: &BinaryOptions) -> Result<Value> {
+    let mut objects = conn.prepare(
+        "SELECT type, name, sql FROM sqlite_schema \
+         WHERE name NOT LIKE 'sqlite_%' AND sql IS NOT NULL ORDER BY name",
+    )?;
+    let objects = objects
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut schema: BTreeMap<&str, serde_json::Map<String, Value>> =
+        ["tables", "indexes", "views", "triggers"]
+            .into_iter()
+            .map(|group| (group, serde_json::Map::new()))
+            .collect();
+    let mut rows = serde_json::Map::new();
+    for (kind, name, sql) in objects {
+        let group = match kind.as_str() {
+            "table" => "tables",
+            "index" => "indexes",
+            "view" => "views",
+            "trigger" => "triggers",
+            _ => continue,
+        };
+        if kind != "table" {
+            let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+            schema
+                .entry(group)
+                .or_default()
+                .insert(escape_reserved_key(name), Value::String(sql));
+            continue;
+        }
+
+        let mut columns = serde_json::Map::new();
+        let mut primary_key = Vec::new();
+        let mut info = conn
+            .prepare("SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1)")?;
+        let mut info_rows = info.query([&name])?;
+        while let Some(row) = info_rows.next()? {
+            let column: String = row.get(0)?;
+            let pk: i64 = row.get(4)?;
+            if pk > 0 {
+                primary_key.push((pk, column.clone()));
+            }
+            let mut definition = serde_json::Map::new();
+            definition.insert("type".to_string(), Value::String(row.get(1)?));
+            definition.insert("not_null".to_string(), Value::Bool(row.get(2)?));
+            definition.insert(
+                "default".to_string(),
+                row.get::<_, Option<String>>(3)?
+                    .map_or(Value::Null, Value::String),
+            );
+            definition.insert("primary_key".to_string(), Value::Bool(pk > 0));
+            columns.insert(escape_reserved_key(column), Value::Object(definition));
+        }
+        primary_key.sort();
+        let key_columns: Vec<String> = if primary_key.is_empty() {
+            vec!["rowid".to_string()]
+        } else {
+            primary_key.into_iter().map(|(_, column)| column).collect()
+        };
+
+        let mut table = serde_json::Map::new();
+        table.insert("columns".to_string(), Value::Object(columns));
+        table.insert(
+            "row_key".to_string(),
+            Value::Array(key_columns.iter().cloned().map(Value::String).collect()),
+        );
+        schema
+            .entry(group)
+            .or_default()
+            .insert(escape_reserved_key(name.clone()), Value::Object(table));
+
+        let quoted = format!("\"{}\"", name.replace('"', "\"\""));
+        let mut select = if key_columns[0] == "rowid" {
+            conn.prepare(&format!("SELECT _rowid_, * FROM {quoted}"))?
+        } else {
+            conn.prepare(&format!("SELECT NULL, * FROM {quoted}"))?
+        };
+        let names: Vec<String> = select
+            .column_names()
+            .into_iter()
+            .skip(1)
+            .map(|column| escape_reserved_key(column.to_string()))
+            .collect();
+        let mut table_rows = select.query([])?;
+        let mut records = Vec::new();
+        while let Some(row) = table_rows.next()? {
+            let mut record = serde_json::Map::new();
+            if key_columns[0] == "rowid" && !names.iter().any(|column| column == "rowid") {
+                record.insert("rowid".to_string(), sqlite_value(row.get_ref(0)?, options));
+            }
+            for (i, column) in names.iter().enumerate() {
+                record.insert(column.clone(), sqlite_value(row.get_ref(i + 1)?, options));
+            }
+            records.push(Value::Object(record));
+        }
+        if let Some(key) = duplicate_row_key(&records, &key_columns) {
+            return Err(anyhow!("Duplicate row key '{name}{key}'"));
+        }
+        rows.insert(escape_reserved_key(name), Value::Array(records));
+    }
+
+    let mut result = serde_json::Map::new();
+    result.insert(
+        "schema".to_string(),
+        Value::Object(
+            schema
+                .into_iter()
+                .map(|(group, objects)| (group.to_string(), Value::Object(objects)))
+                .collect(),
+        ),
+    );
+    result.insert("tables".to_string(), Value::Object(rows));
+    Ok(Value::Object(result))
+}
+
+/// The row keys of the tables of a database read by [`parse_sqlite`], by the path of
+/// each table's rows, e.g. `tables.users` → `["id"]`, for [`DiffConfig::array_id_keys`]
+pub fn sqlite_row_keys(database: &Value) -> BTreeMap<String, Vec<String>> {
+    let Some(tables) = database["schema"]["tables"].as_object() else {
+        return BTreeMap::new();
+    };
+    tables
+        .iter()
+        .filter_map(|(name, table)| {
+            let keys = table["row_key"]
+                .as_array()?
+                .iter()
+                .map(|key| key.as_str().map(str::to_string))
+                .collect::<Option<Vec<String>>>()?;
+            Some((join_key_path("tables", name), keys))
+        })
+        .collect()
+}
+
+fn sqlite_value(value: rusqlite::types::ValueRef<'_>, options: &BinaryOptions) -> Value {
+    use rusqlite::types::ValueRef;
+
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => float_value(f),
+        ValueRef::Text(text) => Value::String(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => bytes_value(bytes, options.bytes_as_hex),
+    }
+}
+
+/// Parse JSON
+pub fn parse_json(content: &str) -> Result<Value> {
+    let mut value: Value = serde_json::from_str(content)?;
+    escape_reserved_keys(&mut value);
+    Ok(value)
+}
+
+/// Parse JSON5 (unquoted keys, single quotes, hex numbers, comments, trailing commas)
+pub fn parse_json5(content: &str) -> Result<Value> {
+    let mut value: Value =
+        json5::from_str(content).map_err(|e| anyhow!("Failed to parse JSON5: {}", e))?;
+    escape_reserved_keys(&mut value);
+    Ok(value)
+}
+
+/// Parse JSON with comments and trailing commas (VS Code settings, tsconfig.json)
+pub fn parse_jsonc(content: &str) -> Result<Value> {
+    parse_json(&strip_json_comments(content))
+}
+
+/// Parse HCL (Terraform `.tf`, `.hcl`) into nested objects.
+///
+/// Blocks are keyed by identifier and then by each label, so
+/// `resource "aws_instance" "web" { ami = "..." }` becomes
+/// `resource.aws_instance.web.ami`. Repeated blocks at the same position become arrays.
+/// Expressions that cannot be evaluated statically (variables, function calls,
+/// conditionals, ...) are kept as their interpolated source text, e.g. `"${var.ami}"`.
+pub fn parse_hcl(content: &str) -> Result<Value> {
+    let body = hcl::parse(content).map_err(|e| anyhow!("Failed to parse HCL: {}", e))?;
+    let mut value = hcl_body_to_value(body)?;
+    escape_reserved_keys(&mut value);
+    Ok(value)
+}
+
+fn hcl_body_to_value(body: hcl::Body) -> Result<Value> {
+    let mut map = serde_json::Map::new();
+
+    for structure in body {
+        match structure {
+            hcl::Structure::Attribute(attr) => {
+                let value = serde_json::to_value(hcl::Value::from(attr.expr))?;
+                map.insert(attr.key.into_inner(), value);
+            }
+            hcl::Structure::Block(block) => {
+                let mut keys: Vec<String> = vec![block.identifier.into_inner()];
+                keys.extend(block.labels.into_iter().map(|label| label.into_inner()));
+                let value = hcl_body_to_value(block.body)?;
+                insert_hcl_block(&mut map, &keys, value)?;
+            }
+        }
+    }
+
+    Ok(Value::Object(map))
+}
+
+fn insert_hcl_block(
+    map: &mut serde_json::Map<String, Value>,
+    keys: &[String],
+    value: Value,
+) -> Result<()> {
+    let (key, rest) = keys.split_first().expect("block has an identifier");
+
+    if rest.is_empty() {
+        match map.get_mut(key) {
+            None => {
+                map.insert(key.clone(), value);
+            }
+            Some(Value::Array(blocks)) => blocks.push(value),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, value]);
+            }
+        }
+        return Ok(());
+    }
+
+    match map
+        .entry(key.clone())
+        .or_insert_with(|| Value::Object(serde_json::Map::new()))
+    {
+        Value::Object(inner) => insert_hcl_block(inner, rest, value),
+        _ => Err(anyhow!(
+            "Failed to parse HCL: block '{}' conflicts with an attribute or unlabeled block",
+            keys.join(".")
+        )),
+    }
+}
+
+/// Options for evaluating Jsonnet
+#[derive(Debug, Clone, Default)]
+pub struct JsonnetOptions {
+    /// Library search paths for `import`, like `jsonnet -J`
+    pub library_paths: Vec<std::path::PathBuf>,
+    /// External variables for `std.extVar`, like `jsonnet --ext-str name=value`
+    pub ext_strs: Vec<(String, String)>,
+}
+
+/// Evaluate a Jsonnet file (`.jsonnet`, `.libsonnet`) to the JSON it renders.
+///
+/// Imports resolve relative to the importing file and then in the library paths.
+/// Errors name the file and line (`config.jsonnet:12:5-20`).
+pub fn evaluate_jsonnet(path: &Path, options: &JsonnetOptions) -> Result<Value> {
+    use jrsonnet_evaluator::manifest::JsonFormat;
+    use jrsonnet_evaluator::trace::{CompactFormat, PathResolver, TraceFormat};
+    use jrsonnet_evaluator::{FileImportResolver, State};
+
+    let context = jrsonnet_stdlib::ContextInitializer::new(PathResolver::new_cwd_fallback());
+    for (name, value) in &options.ext_strs {
+        context.add_ext_str(name.as_str().into(), value.as_str().into());
+    }
+    let mut builder = State::builder();
+    builder
+        .import_resolver(FileImportResolver::new(options.library_paths.clone()))
+        .context_initializer(context);
+    let state = builder.build();
+    let _entered = state.enter();
+    let rendered = state
+        .import(path)
+        .and_then(|value| value.manifest(JsonFormat::default()))
+        .map_err(|e| {
+            let trace = CompactFormat {
+                resolver: PathResolver::new_cwd_fallback(),
+                ..CompactFormat::default()
+            };
+            let message = trace.format(&e).unwrap_or_else(|_| e.to_string());
+            anyhow!("Failed to evaluate Jsonnet: {}", message.trim_end())
+        })?;
+    parse_json(&rendered).map_err(|e| anyhow!("Failed to parse Jsonnet output: {}", e))
+}
+
+/// Parse RON (Rusty Object Notation).
+///
+/// Named structs and enum variants keep their name as the single key of an object,
+/// the way serde writes externally tagged enums: `Window(width: 800)` becomes
+/// `{"Window": {"width": 800}}`, `Move(1, 2)` becomes `{"Move": [1, 2]}` and a unit
+/// variant such as `Fullscreen` becomes the string `"Fullscreen"`. A change of
+/// variant is therefore reported as a change rather than a diff of the fields.
+/// Anonymous structs `(a: 1)` become objects, tuples become arrays, `Some(x)` is `x`
+/// and `None` is null.
+pub fn parse_ron(content: &str) -> Result<Value> {
+    let mut parser = RonParser {
+        chars: content.chars().collect(),
+        pos: 0,
+    };
+    parser.skip_attributes()?;
+    let value = parser.parse_value()?;
+    parser.skip_whitespace()?;
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("unexpected content after the value"));
+    }
+    Ok(value)
+}
+
+struct RonParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl RonParser {
+    fn error(&self, message: &str) -> anyhow::Error {
+        let consumed = &self.chars[..self.pos.min(self.chars.len())];
+        let line = consumed.iter().filter(|&&c| c == '\n').count() + 1;
+        let column = consumed.iter().rev().take_while(|&&c| c != '\n').count() + 1;
+        anyhow!(
+            "Failed to parse RON at line {}, column {}: {}",
+            line,
+            column,
+            message
+        )
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace()?;
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => {
+                self.pos = self.pos.saturating_sub(1);
+                Err(self.error(&format!("expected '{expected}'")))
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<()> {
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some(c), _) if c.is_whitespace() => self.pos += 1,
+                (Some('/'), Some('/')) => while self.next().is_some_and(|c| c != '\n') {},
+                (Some('/'), Some('*')) => {
+                    // Block comments nest, as in Rust
+                    self.pos += 2;
+                    let mut depth = 1;
+                    while depth > 0 {
+                        match (self.next(), self.peek()) {
+                            (Some('/'), Some('*')) => {
+                                self.pos += 1;
+                                depth += 1;
+                            }
+                            (Some('*'), Some('/')) => {
+                                self.pos += 1;
+                                depth -= 1;
+                            }
+                            (Some(_), _) => {}
+                            (None, _) => return Err(self.error("unterminated block comment")),
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Skip `#![enable(...)]` extension attributes; their effects are always accepted
+    fn skip_attributes(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() != Some('#') {
+                return Ok(());
+            }
+            while let Some(c) = self.next() {
+                if c == ']' {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value> {
+        self.skip_whitespace()?;
+        match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                let items = self.parse_sequence(']')?;
+                Ok(Value::Array(items))
+            }
+            Some('{') => self.parse_map(),
+            Some('(') => self.parse_parenthesized(),
+            Some('"') => self.parse_string().map(Value::String),
+            Some('\'') => self.parse_char(),
+            Some('r')
+                if matches!(self.peek_at(1), Some('"' | '#')) && !self.is_raw_identifier() =>
+            {
+                self.pos += 1;
+                self.parse_raw_string().map(Value::String)
+            }
+            Some('b') if self.peek_at(1) == Some('"') => {
+                self.pos += 1;
+                // `\xNN` escapes in byte strings denote bytes, not code points
+                let text = self.parse_string()?;
+                let bytes: Vec<u8> = if text.chars().all(|c| u32::from(c) <= 0xff) {
+                    text.chars().map(|c| u32::from(c) as u8).collect()
+                } else {
+                    text.into_bytes()
+                };
+                Ok(bytes_value(&bytes, false))
+            }
+            Some('b') if self.peek_at(1) == Some('\'') => {
+                self.pos += 1;
+                match self.parse_char()? {
+                    Value::String(c) => Ok(Value::from(c.bytes().next().unwrap_or_default())),
+                    other => Ok(other),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || matches!(c, '+' | '-' | '.') => self.parse_number(),
+            Some(c) if c.is_alphabetic() || c == '_' => self.parse_identifier_value(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn is_raw_identifier(&self) -> bool {
+        self.peek_at(1) == Some('#')
+            && self
+                .peek_at(2)
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+    }
+
+    /// Comma-separated values up to `close`, allowing a trailing comma
+    fn parse_sequence(&mut self, close: char) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                return Ok(items);
+            }
+            items.push(self.parse_value()?);
+            self.skip_whitespace()?;
+            match self.next() {
+                Some(',') => {}
+                Some(c) if c == close => return Ok(items),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error(&format!("expected ',' or '{close}'")));
+                }
+            }
+        }
+    }
+
+    fn parse_map(&mut self) -> Result<Value> {
+        self.expect('{')?;
+        let mut map = serde_json::Map::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                return Ok(Value::Object(map));
+            }
+            let key = match self.parse_value()? {
+                Value::String(key) => key,
+                other => format_value(&other),
+            };
+            self.expect(':')?;
+            map.insert(escape_reserved_key(key), self.parse_value()?);
+            self.skip_whitespace()?;
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Object(map)),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("expected ',' or '}'"));
+                }
+            }
+        }
+    }
+
+    /// `(a: 1, b: 2)` is a struct, anything else in parentheses a tuple
+    fn parse_parenthesized(&mut self) -> Result<Value> {
+        self.expect('(')?;
+        if self.at_field_name()? {
+            self.parse_fields()
+        } else {
+            self.parse_sequence(')').map(Value::Array)
+        }
+    }
+
+    /// Whether the next token is a struct field name followed by `:`
+    fn at_field_name(&mut self) -> Result<bool> {
+        self.skip_whitespace()?;
+        let start = self.pos;
+        let is_field = match self.parse_identifier() {
+            Some(_) => {
+                self.skip_whitespace()?;
+                self.peek() == Some(':')
+            }
+            None => false,
+        };
+        self.pos = start;
+        Ok(is_field)
+    }
+
+    fn parse_fields(&mut self) -> Result<Value> {
+        let mut fields = serde_json::Map::new();
+        loop {
+            self.skip_whitespace()?;
+            if self.peek() == Some(')') {
+                self.pos += 1;
+                return Ok(Value::Object(fields));
+            }
+            let name = self
+                .parse_identifier()
+                .ok_or_else(|| self.error("expected a field name"))?;
+            self.expect(':')?;
+            fields.insert(name, self.parse_value()?);
+            self.skip_whitespace()?;
+            match self.next() {
+                Some(',') => {}
+                Some(')') => return Ok(Value::Object(fields)),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("expected ',' or ')'"));
+                }
+            }
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Option<String> {
+        if self.peek() == Some('r') && self.is_raw_identifier() {
+            self.pos += 2;
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| self.chars[start..self.pos].iter().collect())
+    }
+
+    fn parse_identifier_value(&mut self) -> Result<Value> {
+        let name = self
+            .parse_identifier()
+            .ok_or_else(|| self.error("expected a value"))?;
+        match name.as_str() {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            "None" => return Ok(Value::Null),
+            "inf" => return Ok(float_value(f64::INFINITY)),
+            "NaN" => return Ok(float_value(f64::NAN)),
+            _ => {}
+        }
+
+        self.skip_whitespace()?;
+        if self.peek() != Some('(') {
+            // Unit struct or unit variant
+            return Ok(Value::String(name));
+        }
+        self.pos += 1;
+        let inner = if self.at_field_name()? {
+            self.parse_fields()?
+        } else {
+            let mut items = self.parse_sequence(')')?;
+            if name == "Some" {
+                return match items.len() {
+                    1 => Ok(items.remove(0)),
+                    _ => Err(self.error("Some(...) takes exactly one value")),
+                };
+            }
+            // Newtype structs and variants wrap their single value directly
+            match items.len() {
+                1 => items.remove(0),
+                _ => Value::Array(items),
+            }
+        };
+        Ok(single_key_object(&name, inner))
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(self.parse_escape()?),
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// `r"..."` or `r#"..."#`, after the `r`
+    fn parse_raw_string(&mut self) -> Result<String> {
+        let mut hashes = 0;
+        while self.peek() == Some('#') {
+            self.pos += 1;
+            hashes += 1;
+        }
+        self.expect('"')?;
+        let start = self.pos;
+        loop {
+            match self.next() {
+                Some('"') if (0..hashes).all(|i| self.peek_at(i) == Some('#')) => {
+                    let s = self.chars[start..self.pos - 1].iter().collect();
+                    self.pos += hashes;
+                    return Ok(s);
+                }
+                Some(_) => {}
+                None => return Err(self.error("unterminated raw string")),
+            }
+        }
+    }
+
+    fn parse_char(&mut self) -> Result<Value> {
+        self.expect('\'')?;
+        let c = match self.next() {
+            Some('\\') => self.parse_escape()?,
+            Some(c) => c,
+            None => return Err(self.error("unterminated character")),
+        };
+        match self.next() {
+            Some('\'') => Ok(Value::String(c.to_string())),
+            _ => Err(self.error("unterminated character")),
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char> {
+        match self.next() {
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('0') => Ok('\0'),
+            Some(c @ ('\\' | '"' | '\'')) => Ok(c),
+            Some('x') => {
+                let hex: String = (0..2).filter_map(|_| self.next()).collect();
+                u8::from_str_radix(&hex, 16)
+                    .map(char::from)
+                    .map_err(|_| self.error("invalid \\x escape"))
+            }
+            Some('u') => {
+                self.expect('{')?;
+                let mut hex = String::new();
+                while let Some(c) = self.next() {
+                    if c == '}' {
+                        break;
+                    }
+                    hex.push(c);
+                }
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid \\u escape"))
+            }
+            _ => Err(self.error("invalid escape")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        let negative = self.peek() == Some('-');
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.pos += 1;
+        }
+        if self.peek().is_some_and(char::is_alphabetic) {
+            return match self.parse_identifier().as_deref() {
+                Some("inf") if negative => Ok(float_value(f64::NEG_INFINITY)),
+                Some("inf") => Ok(float_value(f64::INFINITY)),
+                Some("NaN") => Ok(float_value(f64::NAN)),
+                _ => Err(self.error("expected a number")),
+            };
+        }
+
+        let radix = match (self.peek(), self.peek_at(1)) {
+            (Some('0'), Some('x')) => 16,
+            (Some('0'), Some('b')) => 2,
+            (Some('0'), Some('o')) => 8,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.pos += 2;
+        }
+        let digits_start = self.pos;
+        while self.peek().is_some_and(|c| {
+            c.is_ascii_alphanumeric()
+                || c == '_'
+                || (radix == 10 && c == '.')
+                || (radix == 10
+                    && matches!(c, '+' | '-')
+                    && matches!(self.chars[self.pos - 1], 'e' | 'E'))
+        }) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[digits_start..self.pos]
+            .iter()
+            .filter(|&&c| c != '_')
+            .collect();
+        let invalid = || {
+            self.error(&format!(
+                "invalid number '{}'",
+                self.chars[start..self.pos].iter().collect::<String>()
+            ))
+        };
+
+        if radix != 10 {
+            let magnitude = u128::from_str_radix(&digits, radix).map_err(|_| invalid())?;
+            let magnitude = i128::try_from(magnitude).map_err(|_| invalid())?;
+            return Ok(integer_value(if negative { -magnitude } else { magnitude }));
+        }
+        let literal = format!("{}{}", if negative { "-" } else { "" }, digits);
+        if digits.contains(['.', 'e', 'E']) {
+            let float: f64 = literal.parse().map_err(|_| invalid())?;
+            Ok(float_value(float))
+        } else {
+            literal
+                .parse::<serde_json::Number>()
+                .map(Value::Number)
+                .map_err(|_| invalid())
+        }
+    }
+}
+
+/// Options for decoding binary formats (MessagePack, CBOR, BSON) and plists
+#[derive(Debug, Clone, Default)]
+pub struct BinaryOptions {
+    /// Show binary blobs (plist `<data>`) as hex instead of base64
+    pub bytes_as_hex: bool,
+}
+
+/// Binary formats recognised by [`detect_binary_format`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    MessagePack,
+    Cbor,
+    Bson,
+    Plist,
+    Parquet,
+    ArrowIpc,
+    Sqlite,
+}
+
+/// Recognise MessagePack, CBOR, BSON, a binary plist, Parquet, Arrow IPC or a SQLite
+/// database from the content alone.
+///
+/// Content that reads as text (UTF-8 without control characters other than
+/// whitespace) is never reported as binary, since e.g. any ASCII digit is also a
+/// complete MessagePack value. Binary plists, Parquet, Arrow IPC and SQLite files
+/// are recognised by their magic bytes; otherwise the input must decode completely as one of the formats, tried in the
+/// order BSON (length-prefixed, so the least ambiguous), CBOR, MessagePack.
+pub fn detect_binary_format(content: &[u8]) -> Option<BinaryFormat> {
+    if content.is_empty() || looks_like_text(content) {
+        return None;
+    }
+    if content.starts_with(b"bplist") {
+        return Some(BinaryFormat::Plist);
+    }
+    if content.len() >= 8 && content.starts_with(b"PAR1") && content.ends_with(b"PAR1") {
+        return Some(BinaryFormat::Parquet);
+    }
+    if content.starts_with(b"ARROW1") {
+        return Some(BinaryFormat::ArrowIpc);
+    }
+    if content.starts_with(b"SQLite format 3\0") {
+        return Some(BinaryFormat::Sqlite);
+    }
+    let options = BinaryOptions::default();
+    if parse_bson_documents_with_options(content, &options).is_ok() {
+        Some(BinaryFormat::Bson)
+    } else if parse_cbor_with_options(content, &options).is_ok() {
+        Some(BinaryFormat::Cbor)
+    } else if parse_msgpack_with_options(content, &options).is_ok() {
+        Some(BinaryFormat::MessagePack)
+    } else {
+        None
+    }
+}
+
+/// UTF-8 without control characters other than whitespace
+fn looks_like_text(content: &[u8]) -> bool {
+    std::str::from_utf8(content).is_ok_and(|text| {
+        text.chars()
+            .all(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+    })
+}
+
+/// Text formats recognised by [`sniff_text_format`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextFormat {
+    Json,
+    Yaml,
+    Toml,
+    Xml,
+    Ini,
+    Csv,
+}
+
+impl TextFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            TextFormat::Json => "JSON",
+            TextFormat::Yaml => "YAML",
+            TextFormat::Toml => "TOML",
+            TextFormat::Xml => "XML",
+            TextFormat::Ini => "INI",
+            TextFormat::Csv => "CSV",
+        }
+    }
+}
+
+/// Guess the format of text input from its content, for stdin, process
+/// substitution (`/dev/fd/63`) and files without a known extension.
+///
+/// Markup starting with `<` is XML and input that parses as JSON is JSON. Otherwise
+/// each remaining format is a candidate when its structure fits: YAML that parses to
+/// a mapping or sequence, TOML that parses to a non-empty table, INI made only of
+/// `[section]` and `key=value` lines, and CSV with the same number of commas on every
+/// line. TOML is preferred to INI, of which it is a stricter form. Returns `None`
+/// for empty or binary input and when no format fits, and an error naming the
+/// candidates when several do (e.g. `[servers]` alone is a YAML list and a TOML table).
+pub fn sniff_text_format(content: &[u8]) -> Result<Option<TextFormat>> {
+    if !looks_like_text(content) {
+        return Ok(None);
+    }
+    let text = std::str::from_utf8(content)?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let trimmed = text.trim_start();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    if trimmed.starts_with('<') {
+        return Ok(Some(TextFormat::Xml));
+    }
+    if serde_json::from_str::<Value>(trimmed).is_ok() {
+        return Ok(Some(TextFormat::Json));
+    }
+
+    let mut candidates = Vec::new();
+    if parse_yaml_documents(text).is_ok_and(|documents| {
+        documents.iter().any(|document| {
+            document.is_array() || document.is_object() && extension_value(document).is_none()
+        })
+    }) {
+        candidates.push(TextFormat::Yaml);
+    }
+    if parse_toml(text).is_ok_and(|table| table.as_object().is_some_and(|t| !t.is_empty())) {
+        candidates.push(TextFormat::Toml);
+    } else if looks_like_ini(text) {
+        candidates.push(TextFormat::Ini);
+    }
+    if looks_like_csv(text) {
+        candidates.push(TextFormat::Csv);
+    }
+
+    match candidates.as_slice() {
+        [] => Ok(None),
+        [format] => Ok(Some(*format)),
+        _ => {
+            let names: Vec<&str> = candidates.iter().map(|format| format.name()).collect();
+            Err(anyhow!("input could be {}", names.join(" or ")))
+        }
+    }
+}
+
+/// `[section]` and `key=value` lines, comments and blank lines only
+fn looks_like_ini(text: &str) -> bool {
+    let mut has_entry = false;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, _)) if !key.trim().is_empty() && !key.contains(':') => has_entry = true,
+            _ => return false,
+        }
+    }
+    has_entry
+}
+
+/// At least two lines with the same number of commas outside quotes, and none
+/// that reads as a YAML entry (`key: value`, `- item`)
+fn looks_like_csv(text: &str) -> bool {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty()).take(20);
+    let count_fields = |line: &str| {
+        let mut in_quotes = false;
+        let mut commas = 0;
+        for c in line.chars() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ',' if !in_quotes => commas += 1,
+                _ => {}
+            }
+        }
+        commas
+    };
+    let is_yaml_entry = |line: &str| line.contains(": ") || line.trim_start().starts_with("- ");
+    let Some(header) = lines.next() else {
+        return false;
+    };
+    let commas = count_fields(header);
+    let mut rows = 0;
+    for line in lines {
+        if count_fields(line) != commas || is_yaml_entry(line) {
+            return false;
+        }
+        rows += 1;
+    }
+    commas > 0 && rows > 0 && !is_yaml_entry(header)
+}
+
+/// Parse a single MessagePack value.
+///
+/// Binary data becomes a bytes value, the timestamp extension (type -1) a date/time,
+/// and other extension types are tagged `ext:<type>`. Map keys that are not strings
+/// are written in their display form, e.g. `1`.
+pub fn parse_msgpack(content: &[u8]) -> Result<Value> {
+    parse_msgpack_with_options(content, &BinaryOptions::default())
+}
+
+pub fn parse_msgpack_with_options(content: &[u8], options: &BinaryOptions) -> Result<Value> {
+    let mut reader = content;
+    let value = rmpv::decode::read_value(&mut reader)
+        .map_err(|e| anyhow!("Failed to parse MessagePack: {}", e))?;
+    if !reader.is_empty() {
+        return Err(anyhow!(
+            "Failed to parse MessagePack: {} unexpected bytes after the value",
+            reader.len()
+        ));
+    }
+    Ok(msgpack_to_json(value, options))
+}
+
+fn msgpack_to_json(value: rmpv::Value, options: &BinaryOptions) -> Value {
+    use rmpv::Value as Msgpack;
+
+    match value {
+        Msgpack::Nil => Value::Null,
+        Msgpack::Boolean(b) => Value::Bool(b),
+        Msgpack::Integer(i) => match (i.as_i64(), i.as_u64()) {
+            (Some(i), _) => Value::from(i),
+            (None, Some(u)) => Value::from(u),
+            (None, None) => Value::Null,
+        },
+        Msgpack::F32(f) => float_value(f.into()),
+        Msgpack::F64(f) => float_value(f),
+        Msgpack::String(s) => match s.as_str() {
+            Some(s) => Value::String(s.to_string()),
+            // Invalid UTF-8 in a str is kept as its raw bytes
+            None => bytes_value(s.as_bytes(), options.bytes_as_hex),
+        },
+        Msgpack::Binary(bytes) => bytes_value(&bytes, options.bytes_as_hex),
+        Msgpack::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| msgpack_to_json(item, options))
+                .collect(),
+        ),
+        Msgpack::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = match msgpack_to_json(key, options) {
+                        Value::String(key) => key,
+                        other => format_value(&other),
+                    };
+                    let key = escape_reserved_key(key);
+                    (key, msgpack_to_json(value, options))
+                })
+                .collect(),
+        ),
+        Msgpack::Ext(-1, data) => match msgpack_timestamp(&data) {
+            Some((seconds, nanos)) => datetime_value(unix_datetime(seconds, nanos)),
+            None => tagged_value("ext:-1", bytes_value(&data, options.bytes_as_hex)),
+        },
+        Msgpack::Ext(ext_type, data) => tagged_value(
+            format!("ext:{ext_type}"),
+            bytes_value(&data, options.bytes_as_hex),
+        ),
+    }
+}
+
+/// Decode the payload of the MessagePack timestamp extension into seconds and
+/// nanoseconds since the Unix epoch
+fn msgpack_timestamp(data: &[u8]) -> Option<(i64, u32)> {
+    match data.len() {
+        4 => Some((i64::from(u32::from_be_bytes(data.try_into().ok()?)), 0)),
+        8 => {
+            let packed = u64::from_be_bytes(data.try_into().ok()?);
+            Some(((packed & 0x3_ffff_ffff) as i64, (packed >> 34) as u32))
+        }
+        12 => Some((
+            i64::from_be_bytes(data[4..].try_into().ok()?),
+            u32::from_be_bytes(data[..4].try_into().ok()?),
+        )),
+        _ => None,
+    }
+}
+
+/// Format seconds and nanoseconds since the Unix epoch as an RFC 3339 UTC date/time
+fn unix_datetime(seconds: i64, nanos: u32) -> String {
+    let days = seconds.div_euclid(86_400);
+    let secs_of_day = seconds.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    let mut datetime = format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    );
+    if nanos > 0 {
+        let fraction = format!("{nanos:09}");
+        datetime.push('.');
+        datetime.push_str(fraction.trim_end_matches('0'));
+    }
+    datetime.push('Z');
+    datetime
+}
+
+/// Inverse of [`days_from_civil`]
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Parse a single CBOR data item.
+///
+/// Byte strings become bytes values, tag 0 (date/time string) and tag 1 (epoch
+/// time) become date/times, bignums become exact numbers, and other tags are kept
+/// as `tag:<number>`.
+pub fn parse_cbor(content: &[u8]) -> Result<Value> {
+    parse_cbor_with_options(content, &BinaryOptions::default())
+}
+
+pub fn parse_cbor_with_options(content: &[u8], options: &BinaryOptions) -> Result<Value> {
+    let mut reader = content;
+    let value: ciborium::Value = ciborium::de::from_reader(&mut reader)
+        .map_err(|e| anyhow!("Failed to parse CBOR: {}", e))?;
+    if !reader.is_empty() {
+        return Err(anyhow!(
+            "Failed to parse CBOR: {} unexpected bytes after the data item",
+            reader.len()
+        ));
+    }
+    cbor_to_json(value, options)
+}
+
+fn cbor_to_json(value: ciborium::Value, options: &BinaryOptions) -> Result<Value> {
+    use ciborium::Value as Cbor;
+
+    Ok(match value {
+        Cbor::Null => Value::Null,
+        Cbor::Bool(b) => Value::Bool(b),
+        Cbor::Integer(i) => integer_value(i128::from(i)),
+        Cbor::Float(f) => float_value(f),
+        Cbor::Text(s) => Value::String(s),
+        Cbor::Bytes(bytes) => bytes_value(&bytes, options.bytes_as_hex),
+        Cbor::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| cbor_to_json(item, options))
+                .collect::<Result<_>>()?,
+        ),
+        Cbor::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = match cbor_to_json(key, options)? {
+                        Value::String(key) => key,
+                        other => format_value(&other),
+                    };
+                    let key = escape_reserved_key(key);
+                    Ok((key, cbor_to_json(value, options)?))
+                })
+                .collect::<Result<_>>()?,
+        ),
+        Cbor::Tag(0, inner) => match *inner {
+            Cbor::Text(datetime) => datetime_value(datetime),
+            other => tagged_value("tag:0", cbor_to_json(other, options)?),
+        },
+        Cbor::Tag(1, inner) => match *inner {
+            Cbor::Integer(seconds) => match i64::try_from(i128::from(seconds)) {
+                Ok(seconds) => datetime_value(unix_datetime(seconds, 0)),
+                Err(_) => tagged_value("tag:1", integer_value(i128::from(seconds))),
+            },
+            Cbor::Float(seconds) if seconds.is_finite() => {
+                let whole = seconds.floor();
+                let nanos = ((seconds - whole) * 1e9).round() as u32;
+                datetime_value(unix_datetime(whole as i64, nanos.min(999_999_999)))
+            }
+            other => tagged_value("tag:1", cbor_to_json(other, options)?),
+        },
+        // Bignums arrive as tagged byte strings when they exceed the integer range
+        Cbor::Tag(tag @ (2 | 3), inner) => match *inner {
+            Cbor::Bytes(bytes) => bignum_value(&bytes, tag == 3).unwrap_or_else(|| {
+                tagged_value(
+                    format!("tag:{tag}"),
+                    bytes_value(&bytes, options.bytes_as_hex),
+                )
+            }),
+            other => tagged_value(format!("tag:{tag}"), cbor_to_json(other, options)?),
+        },
+        Cbor::Tag(tag, inner) => tagged_value(format!("tag:{tag}"), cbor_to_json(*inner, options)?),
+        other => {
+            return Err(anyhow!(
+                "Failed to parse CBOR: unsupported data item {:?}",
+                other
+            ))
+        }
+    })
+}
+
+fn integer_value(i: i128) -> Value {
+    match (i64::try_from(i), u64::try_from(i)) {
+        (Ok(i), _) => Value::from(i),
+        (_, Ok(u)) => Value::from(u),
+        // Beyond 64 bits, keep the exact digits as an arbitrary-precision number
+        _ => Value::Number(i.to_string().parse().expect("integer literal")),
+    }
+}
+
+/// Decode a CBOR bignum (big-endian magnitude; negative ones denote -1 - n)
+fn bignum_value(bytes: &[u8], negative: bool) -> Option<Value> {
+    let magnitude = bytes.iter().try_fold(0u128, |acc, &byte| {
+        acc.checked_mul(256)?.checked_add(byte.into())
+    })?;
+    let value = if negative {
+        -1 - i128::try_from(magnitude).ok()?
+    } else {
+        i128::try_from(magnitude).ok()?
+    };
+    Some(integer_value(value))
+}
+
+/// Parse a BSON file: a single document, or an array of documents for a
+/// concatenated stream such as a `mongodump` collection
+pub fn parse_bson(content: &[u8]) -> Result<Value> {
+    parse_bson_with_options(content, &BinaryOptions::default())
+}
+
+pub fn parse_bson_with_options(content: &[u8], options: &BinaryOptions) -> Result<Value> {
+    let mut documents = parse_bson_documents_with_options(content, options)?;
+    if documents.len() == 1 {
+        Ok(documents.remove(0))
+    } else {
+        Ok(Value::Array(documents))
+    }
+}
+
+/// Parse each document of a concatenated BSON stream.
+///
+/// ObjectIds, Decimal128 and the other BSON-specific types are kept as tagged values
+/// named after their MongoDB Extended JSON types (`ObjectId "65a1..."`), dates become
+/// date/times and binary data becomes bytes (tagged with its subtype unless generic).
+pub fn parse_bson_documents(content: &[u8]) -> Result<Vec<Value>> {
+    parse_bson_documents_with_options(content, &BinaryOptions::default())
+}
+
+pub fn parse_bson_documents_with_options(
+    content: &[u8],
+    options: &BinaryOptions,
+) -> Result<Vec<Value>> {
+    let mut documents = Vec::new();
+    let mut rest = content;
+    while !rest.is_empty() {
+        // Each document starts with its total length, which lets truncated or foreign
+        // content fail fast instead of being read as a document
+        let length = rest
+            .get(..4)
+            .map(|prefix| i32::from_le_bytes(prefix.try_into().expect("4 bytes")))
+            .filter(|&length| length >= 5 && length as usize <= rest.len())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Failed to parse BSON: invalid document length at byte {}",
+                    content.len() - rest.len()
+                )
+            })? as usize;
+        let (document, tail) = rest.split_at(length);
+        let document = bson::Document::from_reader(document)
+            .map_err(|e| anyhow!("Failed to parse BSON: {}", e))?;
+        documents.push(bson_document_to_json(document, options));
+        rest = tail;
+    }
+    if documents.is_empty() {
+        return Err(anyhow!("Failed to parse BSON: no documents"));
+    }
+    Ok(documents)
+}
+
+fn bson_document_to_json(document: bson::Document, options: &BinaryOptions) -> Value {
+    Value::Object(
+        document
+            .into_iter()
+            .map(|(key, value)| (escape_reserved_key(key), bson_to_json(value, options)))
+            .collect(),
+    )
+}
+
+fn bson_to_json(value: bson::Bson, options: &BinaryOptions) -> Value {
+    use bson::Bson;
+
+    match value {
+        Bson::Null => Value::Null,
+        Bson::Undefined => tagged_value("Undefined", Value::Null),
+        Bson::Boolean(b) => Value::Bool(b),
+        Bson::Int32(i) => Value::from(i),
+        Bson::Int64(i) => Value::from(i),
+        Bson::Double(f) => float_value(f),
+        Bson::String(s) => Value::String(s),
+        Bson::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| bson_to_json(item, options))
+                .collect(),
+        ),
+        Bson::Document(document) => bson_document_to_json(document, options),
+        Bson::DateTime(datetime) => {
+            let millis = datetime.timestamp_millis();
+            let nanos = millis.rem_euclid(1_000) as u32 * 1_000_000;
+            datetime_value(unix_datetime(millis.div_euclid(1_000), nanos))
+        }
+        Bson::ObjectId(oid) => tagged_value("ObjectId", Value::String(oid.to_hex())),
+        Bson::Decimal128(decimal) => tagged_value("Decimal128", Value::String(decimal.to_string())),
+        Bson::Binary(binary) => {
+            let bytes = bytes_value(&binary.bytes, options.bytes_as_hex);
+            match u8::from(binary.subtype) {
+                0 => bytes,
+                subtype => tagged_value(format!("Binary:{subtype:#04x}"), bytes),
+            }
+        }
+        Bson::Timestamp(timestamp) => tagged_value(
+            "Timestamp",
+            serde_json::json!({ "t": timestamp.time, "i": timestamp.increment }),
+        ),
+        Bson::RegularExpression(regex) => tagged_value(
+            "Regex",
+            Value::String(format!("/{}/{}", regex.pattern, regex.options)),
+        ),
+        Bson::JavaScriptCode(code) => tagged_value("Code", Value::String(code)),
+        Bson::JavaScriptCodeWithScope(code) => tagged_value(
+            "Code",
+            serde_json::json!({
+                "code": code.code,
+                "scope": bson_document_to_json(code.scope, options),
+            }),
+        ),
+        Bson::Symbol(symbol) => tagged_value("Symbol", Value::String(symbol)),
+        Bson::MinKey => tagged_value("MinKey", Value::Null),
+        Bson::MaxKey => tagged_value("MaxKey", Value::Null),
+        other => tagged_value("DBPointer", Value::String(other.to_string())),
+    }
+}
+
+/// Parse an Apple property list in the XML, binary (`bplist00`) or OpenStep
+/// encoding.
+///
+/// Dictionaries become objects and arrays arrays; `<date>` becomes a date/time,
+/// `<data>` a bytes value, and `<integer>`/`<real>` numbers, so the `<key>`/value
+/// sibling pairs of the XML encoding are compared as entries of one dictionary.
+/// Keyed-archiver `CF$UID` references are kept as values tagged `UID`.
+pub fn parse_plist(content: &[u8]) -> Result<Value> {
+    parse_plist_with_options(content, &BinaryOptions::default())
+}
+
+pub fn parse_plist_with_options(content: &[u8], options: &BinaryOptions) -> Result<Value> {
+    let value = plist::Value::from_reader(std::io::Cursor::new(content))
+        .map_err(|e| anyhow!("Failed to parse plist: {}", e))?;
+    plist_to_json(value, options)
+}
+
+fn plist_to_json(value: plist::Value, options: &BinaryOptions) -> Result<Value> {
+    use plist::Value as Plist;
+
+    Ok(match value {
+        Plist::Boolean(b) => Value::Bool(b),
+        Plist::Integer(i) => match (i.as_signed(), i.as_unsigned()) {
+            (Some(i), _) => Value::from(i),
+            (None, Some(u)) => Value::from(u),
+            (None, None) => return Err(anyhow!("Failed to parse plist: invalid integer {}", i)),
+        },
+        Plist::Real(f) => float_value(f),
+        Plist::String(s) => Value::String(s),
+        Plist::Date(date) => datetime_value(date.to_xml_format()),
+        Plist::Data(bytes) => bytes_value(&bytes, options.bytes_as_hex),
+        Plist::Uid(uid) => tagged_value("UID", Value::from(uid.get())),
+        Plist::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| plist_to_json(item, options))
+                .collect::<Result<_>>()?,
+        ),
+        Plist::Dictionary(dict) => Value::Object(
+            dict.into_iter()
+                .map(|(key, value)| Ok((escape_reserved_key(key), plist_to_json(value, options)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => {
+            return Err(anyhow!(
+                "Failed to parse plist: unsupported value {:?}",
+                other
+            ))
+        }
+    })
+}
+
+/// Schema for decoding Protocol Buffers messages
+#[derive(Debug, Clone, Default)]
+pub struct ProtobufOptions {
+    /// Serialized `FileDescriptorSet`, e.g. from `protoc --descriptor_set_out` or `buf build -o`
+    pub descriptor_set: Vec<u8>,
+    /// Fully qualified name of the message type, e.g. `shop.v1.Order`
+    pub message: String,
+    /// Show `bytes` fields as hex instead of base64
+    pub bytes_as_hex: bool,
+    /// Binary wire format or text format
+    pub encoding: ProtobufEncoding,
+}
+
+/// Encoding of a Protocol Buffers message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProtobufEncoding {
+    /// The binary wire format, or the text format when the input does not decode as binary
+    #[default]
+    Auto,
+    /// The binary wire format (`.binpb`)
+    Binary,
+    /// The text format (`.txtpb`, `.textproto`)
+    Text,
+}
+
+/// Decode a Protocol Buffers message, in the binary wire format or the text format,
+/// using the message type from a descriptor set.
+///
+/// Fields are keyed by name and enums written by value name. Unknown fields are kept
+/// under their field number, so a field missing from the descriptor still shows up
+/// in the diff. As in the JSON mapping, fields that are not set are left out.
+pub fn parse_protobuf(content: &[u8], options: &ProtobufOptions) -> Result<Value> {
+    use prost_reflect::{DescriptorPool, DynamicMessage};
+
+    let pool = DescriptorPool::decode(options.descriptor_set.as_slice())
+        .map_err(|e| anyhow!("Failed to load protobuf descriptor set: {}", e))?;
+    let message_name = options.message.trim_start_matches('.');
+    let descriptor = pool.get_message_by_name(message_name).ok_or_else(|| {
+        anyhow!(
+            "Message type '{}' is not in the protobuf descriptor set",
+            message_name
+        )
+    })?;
+
+    let binary = |descriptor| {
+        DynamicMessage::decode(descriptor, content)
+            .map_err(|e| anyhow!("Failed to decode protobuf message: {}", e))
+    };
+    let text = |descriptor| {
+        let text = std::str::from_utf8(content)
+            .map_err(|_| anyhow!("Failed to parse protobuf text format: input is not UTF-8"))?;
+        DynamicMessage::parse_text_format(descriptor, text)
+            .map_err(|e| anyhow!("Failed to parse protobuf text format: {}", e))
+    };
+    // Printable bytes can still be a valid binary message, so binary is tried first
+    let message = match options.encoding {
+        ProtobufEncoding::Binary => binary(descriptor)?,
+        ProtobufEncoding::Text => text(descriptor)?,
+        ProtobufEncoding::Auto => match binary(descriptor.clone()) {
+            Ok(message) => message,
+            Err(_) if looks_like_text(content) => text(descriptor)?,
+            Err(e) => return Err(e),
+        },
+    };
+    Ok(protobuf_message_to_json(&message, options))
+}
+
+fn protobuf_message_to_json(
+    message: &prost_reflect::DynamicMessage,
+    options: &ProtobufOptions,
+) -> Value {
+    let mut map = serde_json::Map::new();
+    for (field, value) in message.fields() {
+        map.insert(
+            field.name().to_string(),
+            protobuf_value_to_json(value, &field.kind(), options),
+        );
+    }
+    for unknown in message.unknown_fields() {
+        let value = protobuf_unknown_value(unknown, options);
+        match map.entry(unknown.number().to_string()) {
+            serde_json::map::Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+            // Repeated unknown fields collect into an array
+            serde_json::map::Entry::Occupied(mut entry) => match entry.get_mut() {
+                Value::Array(items) => items.push(value),
+                existing => *existing = Value::Array(vec![existing.take(), value]),
+            },
+        }
+    }
+    Value::Object(map)
+}
+
+fn protobuf_value_to_json(
+    value: &prost_reflect::Value,
+    kind: &prost_reflect::Kind,
+    options: &ProtobufOptions,
+) -> Value {
+    use prost_reflect::{Kind, MapKey, Value as Proto};
+
+    match value {
+        Proto::Bool(b) => Value::Bool(*b),
+        Proto::I32(i) => Value::from(*i),
+        Proto::I64(i) => Value::from(*i),
+        Proto::U32(u) => Value::from(*u),
+        Proto::U64(u) => Value::from(*u),
+        // Through the shortest decimal spelling, so that 0.1f stays 0.1
+        Proto::F32(f) => float_value(f.to_string().parse().unwrap_or(f64::NAN)),
+        Proto::F64(f) => float_value(*f),
+        Proto::String(s) => Value::String(s.clone()),
+        Proto::Bytes(bytes) => bytes_value(bytes, options.bytes_as_hex),
+        Proto::EnumNumber(number) => match kind {
+            Kind::Enum(enum_descriptor) => match enum_descriptor.get_value(*number) {
+                Some(enum_value) => Value::String(enum_value.name().to_string()),
+                None => Value::from(*number),
+            },
+            _ => Value::from(*number),
+        },
+        Proto::Message(message) => protobuf_message_to_json(message, options),
+        Proto::List(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| protobuf_value_to_json(item, kind, options))
+                .collect(),
+        ),
+        Proto::Map(entries) => {
+            let value_kind = match kind {
+                Kind::Message(entry) => entry.map_entry_value_field().kind(),
+                other => other.clone(),
+            };
+            // Map order is not significant on the wire; sort for stable output
+            let sorted: BTreeMap<String, Value> = entries
+                .iter()
+                .map(|(key, value)| {
+                    let key = match key {
+                        MapKey::Bool(b) => b.to_string(),
+                        MapKey::I32(i) => i.to_string(),
+                        MapKey::I64(i) => i.to_string(),
+                        MapKey::U32(u) => u.to_string(),
+                        MapKey::U64(u) => u.to_string(),
+                        MapKey::String(s) => escape_reserved_key(s.clone()),
+                    };
+                    (key, protobuf_value_to_json(value, &value_kind, options))
+                })
+                .collect();
+            Value::Object(sorted.into_iter().collect())
+        }
+    }
+}
+
+/// Read the value of a field missing from the descriptor back from its encoding
+fn protobuf_unknown_value(
+    unknown: &prost_reflect::UnknownField,
+    options: &ProtobufOptions,
+) -> Value {
+    use prost::encoding::{decode_varint, WireType};
+
+    let mut encoded = Vec::new();
+    unknown.encode(&mut encoded);
+    let mut buf = encoded.as_slice();
+    // Skip the field key
+    if decode_varint(&mut buf).is_err() {
+        return Value::Null;
+    }
+    match unknown.wire_type() {
+        WireType::Varint => decode_varint(&mut buf).map(Value::from).unwrap_or_default(),
+        WireType::SixtyFourBit => buf
+            .try_into()
+            .map(|bytes| Value::from(u64::from_le_bytes(bytes)))
+            .unwrap_or_default(),
+        WireType::ThirtyTwoBit => buf
+            .try_into()
+            .map(|bytes| Value::from(u32::from_le_bytes(bytes)))
+            .unwrap_or_default(),
+        WireType::LengthDelimited => match decode_varint(&mut buf) {
+            Ok(_) => bytes_value(buf, options.bytes_as_hex),
+            Err(_) => Value::Null,
+        },
+        WireType::StartGroup | WireType::EndGroup => bytes_value(buf, options.bytes_as_hex),
+    }
+}
+
+/// Options for parsing Java `.properties` files
+#[derive(Debug, Clone, Default)]
+pub struct PropertiesOptions {
+    /// Expand dotted keys (`spring.datasource.url`) and indices (`hosts[0]`) into
+    /// nested objects and arrays, matching the equivalent YAML structure
+    pub expand_dotted_keys: bool,
+    /// Read `true`/`false` and numeric values as booleans and numbers, so that
+    /// `server.port=8080` matches `port: 8080` in YAML
+    pub coerce_scalars: bool,
+}
+
+/// Parse a Java `.properties` file into a flat object of string values
+pub fn parse_properties(content: &str) -> Result<Value> {
+    parse_properties_with_options(content, &PropertiesOptions::default())
+}
+
+/// Parse a Java `.properties` file.
+///
+/// Supports `key=value`, `key: value` and `key value` separators, `#`/`!` comments,
+/// backslash line continuations and `\uXXXX` escapes. Later duplicates win, as in
+/// `java.util.Properties`.
+pub fn parse_properties_with_options(content: &str, options: &PropertiesOptions) -> Result<Value> {
+    let mut map = serde_json::Map::new();
+
+    for (line_number, logical_line) in properties_logical_lines(content) {
+        let (raw_key, raw_value) = split_property_line(&logical_line);
+        let key = unescape_property(raw_key)
+            .map_err(|e| anyhow!("Failed to parse properties at line {}: {}", line_number, e))?;
+        let value = unescape_property(raw_value)
+            .map_err(|e| anyhow!("Failed to parse properties at line {}: {}", line_number, e))?;
+        let value = if options.coerce_scalars {
+            coerce_property_scalar(value)
+        } else {
+            Value::String(value)
+        };
+        map.insert(key, value);
+    }
+
+    let mut value = if options.expand_dotted_keys {
+        let mut root = Value::Object(serde_json::Map::new());
+        for (key, value) in map {
+            insert_dotted_key(&mut root, &key, value)?;
+        }
+        root
+    } else {
+        Value::Object(map)
+    };
+    escape_reserved_keys(&mut value);
+    Ok(value)
+}
+
+/// Turn a properties value into a boolean or number when it reads as one.
+/// Numbers follow JSON syntax, so `0755` or `+1` stay strings.
+fn coerce_property_scalar(value: String) -> Value {
+    if value.eq_ignore_ascii_case("true") {
+        return Value::Bool(true);
+    }
+    if value.eq_ignore_ascii_case("false") {
+        return Value::Bool(false);
+    }
+    match serde_json::from_str::<Value>(&value) {
+        Ok(number @ Value::Number(_)) if value.trim() == value => number,
+        _ => Value::String(value),
+    }
+}
+
+/// Join continuation lines and drop comments and blank lines.
+/// Yields the 1-based line number where each logical line starts.
+fn properties_logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start_matches([' ', '\t', '\x0c']);
+        let (start, mut text) = match current.take() {
+            Some((start, text)) => (start, text),
+            None => {
+                if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+                    continue;
+                }
+                (index + 1, String::new())
+            }
+        };
+
+        // An odd number of trailing backslashes continues the line
+        let trailing = trimmed.chars().rev().take_while(|&c| c == '\\').count();
+        if trailing % 2 == 1 {
+            text.push_str(&trimmed[..trimmed.len() - 1]);
+            current = Some((start, text));
+        } else {
+            text.push_str(trimmed);
+            lines.push((start, text));
+        }
+    }
+
+    if let Some(pending) = current {
+        lines.push(pending);
+    }
+    lines
+}
+
+/// Split a logical line into its (still escaped) key and value
+fn split_property_line(line: &str) -> (&str, &str) {
+    let mut key_end = line.len();
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '=' | ':' | ' ' | '\t' | '\x0c' => {
+                key_end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let key = &line[..key_end];
+    let rest = line[key_end..].trim_start_matches([' ', '\t', '\x0c']);
+    let rest = rest
+        .strip_prefix('=')
+        .or_else(|| rest.strip_prefix(':'))
+        .map(|r| r.trim_start_matches([' ', '\t', '\x0c']))
+        .unwrap_or(rest);
+    (key, rest)
+}
+
+fn unescape_property(raw: &str) -> Result<String> {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\x0c'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let code = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 4)
+                    .ok_or_else(|| anyhow!("malformed \\u escape: \\u{}", hex))?;
+                let unit = u16::try_from(code).expect("four hex digits fit in u16");
+                // Surrogate pairs are written as two consecutive \uXXXX escapes
+                if (0xD800..0xDC00).contains(&unit) {
+                    let rest = chars.as_str();
+                    let low = rest
+                        .strip_prefix("\\u")
+                        .and_then(|r| r.get(..4))
+                        .and_then(|h| u16::from_str_radix(h, 16).ok())
+                        .filter(|low| (0xDC00..0xE000).contains(low))
+                        .ok_or_else(|| anyhow!("unpaired surrogate \\u{}", hex))?;
+                    chars = rest[6..].chars();
+                    out.extend(char::decode_utf16([unit, low]).map(|r| r.unwrap()));
+                } else {
+                    out.push(
+                        char::from_u32(code)
+                            .ok_or_else(|| anyhow!("invalid \\u escape: \\u{}", hex))?,
+                    );
+                }
+            }
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+
+    Ok(out)
+}
+
+/// Insert `value` at a dotted path such as `servers[0].host`, creating
+/// intermediate objects and arrays as needed
+fn insert_dotted_key(root: &mut Value, key: &str, value: Value) -> Result<()> {
+    enum Segment<'a> {
+        Key(&'a str),
+        Index(usize),
+    }
+
+    let mut segments = Vec::new();
+    for part in key.split('.') {
+        let (name, mut indices) = match part.find('[') {
+            Some(pos) if part.ends_with(']') => (&part[..pos], &part[pos..]),
+            _ => (part, ""),
+        };
+        let mut part_segments = vec![Segment::Key(name)];
+        while let Some(stripped) = indices.strip_prefix('[') {
+            let close = stripped.find(']').unwrap_or(stripped.len());
+            match stripped[..close].parse::<usize>() {
+                Ok(index) => part_segments.push(Segment::Index(index)),
+                // Not an index after all: keep the whole part as a plain key
+                Err(_) => {
+                    part_segments = vec![Segment::Key(part)];
+                    break;
+                }
+            }
+            indices = stripped.get(close + 1..).unwrap_or("");
+        }
+        segments.extend(part_segments);
+    }
+
+    let conflict = || anyhow!("Conflicting property keys at '{}'", key);
+    let mut current = root;
+    for (i, segment) in segments.iter().enumerate() {
+        let is_last = i + 1 == segments.len();
+        let next_is_index = matches!(segments.get(i + 1), Some(Segment::Index(_)));
+        let empty_container = || {
+            if next_is_index {
+                Value::Array(Vec::new())
+            } else {
+                Value::Object(serde_json::Map::new())
+            }
+        };
+
+        current = match segment {
+            Segment::Key(name) => {
+                let map = current.as_object_mut().ok_or_else(conflict)?;
+                if is_last {
+                    if map
+                        .get(*name)
+                        .is_some_and(|v| v.is_object() || v.is_array())
+                    {
+                        return Err(conflict());
+                    }
+                    map.insert(name.to_string(), value);
+                    return Ok(());
+                }
+                map.entry(name.to_string()).or_insert_with(empty_container)
+            }
+            Segment::Index(index) => {
+                let array = current.as_array_mut().ok_or_else(conflict)?;
+                if array.len() <= *index {
+                    array.resize(*index + 1, Value::Null);
+                }
+                if is_last {
+                    if array[*index].is_object() || array[*index].is_array() {
+                        return Err(conflict());
+                    }
+                    array[*index] = value;
+                    return Ok(());
+                }
+                if array[*index].is_null() {
+                    array[*index] = empty_container();
+                }
+                &mut array[*index]
+            }
+        };
+        if !(current.is_object() || current.is_array()) {
+            return Err(conflict());
+        }
+    }
+
+    Ok(())
+}
+
+/// Options for reading Markdown front matter
+#[derive(Debug, Clone, Default)]
+pub struct FrontMatterOptions {
+    /// Also compare the Markdown body, as one string
+    pub include_body: bool,
+}
+
+/// Parse the front matter of a Markdown file: a YAML block between `---` lines
+/// (closed by `---` or `...`) or a TOML block between `+++` lines at the very top.
+///
+/// The result is the metadata object, empty when the file has no front matter.
+/// With `include_body` it is `{"front_matter": ..., "body": "..."}` instead, the body
+/// being everything after the closing line.
+pub fn parse_front_matter(content: &str, options: &FrontMatterOptions) -> Result<Value> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines = content.split_inclusive('\n');
+    let opening = lines.next().map(str::trim_end);
+    let closings: &[&str] = match opening {
+        Some("---") => &["---", "..."],
+        Some("+++") => &["+++"],
+        _ => &[],
+    };
+
+    let mut metadata = Value::Object(serde_json::Map::new());
+    let mut body = content;
+    if !closings.is_empty() {
+        let start = content.find('\n').map_or(content.len(), |i| i + 1);
+        let mut offset = start;
+        let mut end = None;
+        for line in lines {
+            if closings.contains(&line.trim_end()) {
+                end = Some((offset, offset + line.len()));
+                break;
+            }
+            offset += line.len();
+        }
+        let (block_end, body_start) =
+            end.ok_or_else(|| anyhow!("Front matter opened with '{}' is not closed", closings[0]))?;
+        let block = &content[start..block_end];
+        let parsed = if block.trim().is_empty() {
+            Value::Null
+        } else if closings[0] == "+++" {
+            parse_toml(block).map_err(|e| anyhow!("Failed to parse TOML front matter: {}", e))?
+        } else {
+            parse_yaml(block).map_err(|e| anyhow!("Failed to parse YAML front matter: {}", e))?
+        };
+        if !parsed.is_null() {
+            metadata = parsed;
+        }
+        body = &content[body_start..];
+    }
+
+    if options.include_body {
+        let mut result = serde_json::Map::new();
+        result.insert("front_matter".to_string(), metadata);
+        result.insert("body".to_string(), Value::String(body.to_string()));
+        Ok(Value::Object(result))
+    } else {
+        Ok(metadata)
+    }
+}
+
+/// Parse a dotenv (`.env`) file into an object of string values.
+///
+/// Supports `KEY=value` and `export KEY=value`, `#` comments (inline comments need a
+/// preceding space), single-quoted literal values, and double-quoted values with
+/// `\n`, `\t`, `\r`, `\"`, `\\` and `\$` escapes. Quoted values may span multiple lines.
+/// Variable references such as `${HOME}` are kept verbatim, not expanded.
+pub fn parse_dotenv(content: &str) -> Result<Value> {
+    let mut map = serde_json::Map::new();
+    let mut chars = content.chars().peekable();
+    let mut line = 1;
+
+    let skip_inline_space = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        while matches!(chars.peek(), Some(' ' | '\t')) {
+            chars.next();
+        }
+    };
+
+    loop {
+        // Skip blank lines and leading whitespace
+        while let Some(&c) = chars.peek() {
+            match c {
+                '\n' => line += 1,
+                ' ' | '\t' | '\r' => {}
+                _ => break,
+            }
+            chars.next();
+        }
+        let Some(&first) = chars.peek() else {
+            break;
+        };
+
+        if first == '#' {
+            for c in chars.by_ref() {
+                if c == '\n' {
+                    line += 1;
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let start_line = line;
+        let mut key = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == '=' || c == '\n' || c == ' ' || c == '\t' {
+                break;
+            }
+            key.push(c);
+            chars.next();
+        }
+        skip_inline_space(&mut chars);
+        if key == "export" && chars.peek() != Some(&'=') {
+            key.clear();
+            while let Some(&c) = chars.peek() {
+                if c == '=' || c == '\n' || c == ' ' || c == '\t' {
+                    break;
+                }
+                key.push(c);
+                chars.next();
+            }
+            skip_inline_space(&mut chars);
+        }
+
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        {
+            return Err(anyhow!(
+                "Failed to parse dotenv at line {}: invalid key '{}'",
+                start_line,
+                key
+            ));
+        }
+        if chars.next_if_eq(&'=').is_none() {
+            return Err(anyhow!(
+                "Failed to parse dotenv at line {}: expected '=' after '{}'",
+                start_line,
+                key
+            ));
+        }
+        skip_inline_space(&mut chars);
+
+        let mut value = String::new();
+        match chars.peek() {
+            Some(&quote @ ('\'' | '"')) => {
+                chars.next();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        c if c == quote => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' if quote == '"' => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some('r') => value.push('\r'),
+                            Some(escaped @ ('"' | '\\' | '$')) => value.push(escaped),
+                            Some(other) => {
+                                if other == '\n' {
+                                    line += 1;
+                                }
+                                value.push('\\');
+                                value.push(other);
+                            }
+                            None => value.push('\\'),
+                        },
+                        c => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                    }
+                }
+                if !closed {
+                    return Err(anyhow!(
+                        "Failed to parse dotenv at line {}: unterminated quoted value for '{}'",
+                        start_line,
+                        key
+                    ));
+                }
+                // Only whitespace or a comment may follow the closing quote
+                skip_inline_space(&mut chars);
+                match chars.peek() {
+                    None | Some('\n' | '\r' | '#') => {}
+                    Some(_) => {
+                        return Err(anyhow!(
+                            "Failed to parse dotenv at line {}: unexpected characters after quoted value for '{}'",
+                            line,
+                            key
+                        ));
+                    }
+                }
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+            }
+            _ => {
+                let mut previous = ' ';
+                while let Some(c) = chars.next() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                    if c == '#' && (previous == ' ' || previous == '\t') {
+                        for c in chars.by_ref() {
+                            if c == '\n' {
+                                line += 1;
+                                break;
+                            }
+                        }
+                        break;
+                    }
+                    value.push(c);
+                    previous = c;
+                }
+                value.truncate(value.trim_end().len());
+            }
+        }
+
+        map.insert(escape_reserved_key(key), Value::String(value));
+    }
+
+    Ok(Value::Object(map))
+}
+
+/// Remove `//` and `/* */` comments and trailing commas so the result is plain JSON.
+/// Comments are replaced by whitespace to keep line numbers in error messages intact.
+pub fn strip_json_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                // Line comment: skip to end of line, keep the newline
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    out.push(if next == '\n' { '\n' } else { ' ' });
+                    prev = next;
+                }
+            }
+            '}' | ']' => {
+                // Drop a trailing comma before the closing bracket
+                let trimmed_len = out.trim_end().len();
+                if out[..trimmed_len].ends_with(',') {
+                    out.replace_range(trimmed_len - 1..trimmed_len, " ");
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Compression formats of inputs that are decompressed before parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// Recognise a compressed stream by its magic bytes. bzip2 needs its whole
+    /// 10-byte header, since `BZh` alone could just as well start a text file.
+    pub fn from_magic(content: &[u8]) -> Option<Self> {
+        if content.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if content.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else if is_bzip2_header(content) {
+            Some(Compression::Bzip2)
+        } else if content.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else {
+            None
+        }
+    }
+
+    /// Recognise a compressed file by its extension (`.gz`, `.zst`, `.bz2`, `.xz`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            "bz2" => Some(Compression::Bzip2),
+            "xz" => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+        }
+    }
+}
+
+/// `BZh`, the block size (`1` to `9`), then the magic of the first block, or of the
+/// end of the stream when it is empty
+fn is_bzip2_header(content: &[u8]) -> bool {
+    const BLOCK_MAGIC: [u8; 6] = [0x31, 0x41, 0x59, 0x26, 0x53, 0x59];
+    const END_MAGIC: [u8; 6] = [0x17, 0x72, 0x45, 0x38, 0x50, 0x90];
+    match content {
+        [b'B', b'Z', b'h', level, magic @ ..] if (b'1'..=b'9').contains(level) => {
+            magic.starts_with(&BLOCK_MAGIC) || magic.starts_with(&END_MAGIC)
+        }
+        _ => false,
+    }
+}
+
+/// Read an input to the end, decompressing it on the fly when it starts with the
+/// magic bytes of a gzip, zstd, bzip2 or xz stream, or else when `hint` names one
+/// (e.g. from [`Compression::from_path`]). Returns the content and the compression
+/// that was undone, if any. Concatenated gzip members are read as one stream.
+pub fn read_decompressed(
+    mut reader: impl Read,
+    hint: Option<Compression>,
+) -> Result<(Vec<u8>, Option<Compression>)> {
+    // Only the magic bytes are read ahead; the rest is streamed through the decoder
+    let mut magic = Vec::with_capacity(10);
+    reader.by_ref().take(10).read_to_end(&mut magic)?;
+    let compression = Compression::from_magic(&magic).or(hint);
+    let reader = std::io::Cursor::new(magic).chain(reader);
+
+    let mut decoder: Box<dyn Read> = match compression {
+        None => Box::new(reader),
+        Some(Compression::Gzip) => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+        Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        Some(Compression::Bzip2) => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+        Some(Compression::Xz) => Box::new(xz2::read::XzDecoder::new_multi_decoder(reader)),
+    };
+    let mut content = Vec::new();
+    decoder
+        .read_to_end(&mut content)
+        .map_err(|e| match compression {
+            Some(compression) => {
+                anyhow!("Failed to decompress {} input: {}", compression.name(), e)
+            }
+            None => e.into(),
+        })?;
+    Ok((content, compression))
 }
 
 /// Parse large files with streaming support to reduce memory usage
@@ -758,11 +5659,11 @@ pub fn parse_large_file<P: AsRef<Path>>(path: P) -> Result<Option<Value>> {
     // Auto-detect format from file extension
     let path_str = path.as_ref().to_string_lossy();
     if path_str.ends_with(".json") {
-        Ok(Some(serde_json::from_str(&content)?))
+        Ok(Some(parse_json(&content)?))
     } else if path_str.ends_with(".yaml") || path_str.ends_with(".yml") {
-        Ok(Some(serde_yml::from_str(&content)?))
+        Ok(Some(parse_yaml(&content)?))
     } else if path_str.ends_with(".toml") {
-        Ok(Some(toml::from_str(&content)?))
+        Ok(Some(parse_toml(&content)?))
     } else {
         Err(anyhow!("Unsupported file format for large file parsing"))
     }
@@ -819,14 +5720,14 @@ fn streaming_diff<P: AsRef<Path>>(
     reader2.read_to_string(&mut buffer2)?;
 
     // Parse with reduced memory footprint
-    let v1: Value = serde_json::from_str(&buffer1)
-        .or_else(|_| serde_yml::from_str(&buffer1))
-        .or_else(|_| toml::from_str(&buffer1))
+    let v1: Value = parse_json(&buffer1)
+        .or_else(|_| parse_yaml(&buffer1))
+        .or_else(|_| parse_toml(&buffer1))
         .map_err(|e| anyhow!("Failed to parse file 1: {}", e))?;
 
-    let v2: Value = serde_json::from_str(&buffer2)
-        .or_else(|_| serde_yml::from_str(&buffer2))
-        .or_else(|_| toml::from_str(&buffer2))
+    let v2: Value = parse_json(&buffer2)
+        .or_else(|_| parse_yaml(&buffer2))
+        .or_else(|_| parse_toml(&buffer2))
         .map_err(|e| anyhow!("Failed to parse file 2: {}", e))?;
 
     // Clear buffers to free memory
@@ -840,9 +5741,10 @@ fn streaming_diff<P: AsRef<Path>>(
         &mut results,
         ignore_keys_regex,
         epsilon,
-        array_id_key,
+        ArrayIdKeys::new(array_id_key),
         false, // ignore_whitespace - not supported in streaming mode
         false, // ignore_case - not supported in streaming mode
+        false, // check_key_order - not supported in streaming mode
     );
 
     Ok(results)
@@ -856,20 +5758,17 @@ fn memory_efficient_diff(
     results: &mut Vec<DiffResult>,
     ignore_keys_regex: Option<&Regex>,
     epsilon: Option<f64>,
-    array_id_key: Option<&str>,
+    array_ids: ArrayIdKeys,
     ignore_whitespace: bool,
     ignore_case: bool,
+    check_key_order: bool,
 ) {
+    let (v1, v2) = decoded_pair(v1, v2);
     // Process diff without cloning large values when possible
-    if !values_are_equal_with_config(v1, v2, epsilon, ignore_whitespace, ignore_case) {
-        let type_match = matches!(
-            (v1, v2),
-            (Value::Null, Value::Null)
-                | (Value::Bool(_), Value::Bool(_))
-                | (Value::Number(_), Value::Number(_))
-                | (Value::Array(_), Value::Array(_))
-                | (Value::Object(_), Value::Object(_))
-        );
+    if check_key_order && is_nested_pair(v1, v2)
+        || !values_are_equal_with_config(v1, v2, epsilon, ignore_whitespace, ignore_case)
+    {
+        let type_match = value_type_name(v1) == value_type_name(v2);
 
         if !type_match {
             results.push(DiffResult::TypeChanged(
@@ -877,7 +5776,7 @@ fn memory_efficient_diff(
                 v1.clone(),
                 v2.clone(),
             ));
-        } else if v1.is_object() && v2.is_object() {
+        } else if is_nested_pair(v1, v2) && v1.is_object() {
             memory_efficient_diff_objects(
                 "",
                 v1.as_object().unwrap(),
@@ -885,9 +5784,10 @@ fn memory_efficient_diff(
                 results,
                 ignore_keys_regex,
                 epsilon,
-                array_id_key,
+                array_ids,
                 ignore_whitespace,
                 ignore_case,
+                check_key_order,
             );
         } else if v1.is_array() && v2.is_array() {
             memory_efficient_diff_arrays(
@@ -897,9 +5797,10 @@ fn memory_efficient_diff(
                 results,
                 ignore_keys_regex,
                 epsilon,
-                array_id_key,
+                array_ids,
                 ignore_whitespace,
                 ignore_case,
+                check_key_order,
             );
         } else {
             results.push(DiffResult::Modified("".to_string(), v1.clone(), v2.clone()));
@@ -916,34 +5817,31 @@ fn memory_efficient_diff_objects(
     results: &mut Vec<DiffResult>,
     ignore_keys_regex: Option<&Regex>,
     epsilon: Option<f64>,
-    array_id_key: Option<&str>,
+    array_ids: ArrayIdKeys,
     ignore_whitespace: bool,
     ignore_case: bool,
+    check_key_order: bool,
 ) {
     // Process keys in batches to limit memory usage
     const BATCH_SIZE: usize = 1000;
 
-    let keys1: Vec<_> = map1.keys().collect();
-    let keys2: Vec<_> = map2.keys().collect();
+    let keys = ordered_key_union(map1, map2);
 
     // Process in batches
-    for chunk in keys1.chunks(BATCH_SIZE) {
+    for chunk in keys.chunks(BATCH_SIZE) {
         for key in chunk {
             if let Some(regex) = ignore_keys_regex {
-                if regex.is_match(key) {
+                if map1.contains_key(*key) && regex.is_match(unescape_reserved_key(key)) {
                     continue;
                 }
             }
 
-            let current_path = if path.is_empty() {
-                (*key).clone()
-            } else {
-                format!("{path}.{key}")
-            };
+            let current_path = join_key_path(path, key);
 
             match (map1.get(*key), map2.get(*key)) {
                 (Some(value1), Some(value2)) => {
-                    if value1.is_object() && value2.is_object() {
+                    let (value1, value2) = decoded_pair(value1, value2);
+                    if is_nested_pair(value1, value2) && value1.is_object() {
                         memory_efficient_diff_objects(
                             &current_path,
                             value1.as_object().unwrap(),
@@ -951,9 +5849,10 @@ fn memory_efficient_diff_objects(
                             results,
                             ignore_keys_regex,
                             epsilon,
-                            array_id_key,
+                            array_ids,
                             ignore_whitespace,
                             ignore_case,
+                            check_key_order,
                         );
                     } else if value1.is_array() && value2.is_array() {
                         memory_efficient_diff_arrays(
@@ -963,9 +5862,10 @@ fn memory_efficient_diff_objects(
                             results,
                             ignore_keys_regex,
                             epsilon,
-                            array_id_key,
+                            array_ids,
                             ignore_whitespace,
                             ignore_case,
+                            check_key_order,
                         );
                     } else if !values_are_equal_with_config(
                         value1,
@@ -974,15 +5874,7 @@ fn memory_efficient_diff_objects(
                         ignore_whitespace,
                         ignore_case,
                     ) {
-                        let type_match = matches!(
-                            (value1, value2),
-                            (Value::Null, Value::Null)
-                                | (Value::Bool(_), Value::Bool(_))
-                                | (Value::Number(_), Value::Number(_))
-                                | (Value::String(_), Value::String(_))
-                                | (Value::Array(_), Value::Array(_))
-                                | (Value::Object(_), Value::Object(_))
-                        );
+                        let type_match = value_type_name(value1) == value_type_name(value2);
 
                         if !type_match {
                             results.push(DiffResult::TypeChanged(
@@ -1002,8 +5894,8 @@ fn memory_efficient_diff_objects(
                 (Some(value1), None) => {
                     results.push(DiffResult::Removed(current_path, value1.clone()));
                 }
-                (None, Some(_)) => {
-                    // Will be handled in the "added" phase
+                (None, Some(value2)) => {
+                    results.push(DiffResult::Added(current_path, value2.clone()));
                 }
                 (None, None) => {
                     // Should not happen
@@ -1012,20 +5904,8 @@ fn memory_efficient_diff_objects(
         }
     }
 
-    // Process added keys
-    for chunk in keys2.chunks(BATCH_SIZE) {
-        for key in chunk {
-            if !map1.contains_key(*key) {
-                let current_path = if path.is_empty() {
-                    (*key).clone()
-                } else {
-                    format!("{path}.{key}")
-                };
-                if let Some(value2) = map2.get(*key) {
-                    results.push(DiffResult::Added(current_path, value2.clone()));
-                }
-            }
-        }
+    if check_key_order {
+        check_object_key_order(path, map1, map2, results, ignore_keys_regex);
     }
 }
 
@@ -1038,9 +5918,10 @@ fn memory_efficient_diff_arrays(
     results: &mut Vec<DiffResult>,
     ignore_keys_regex: Option<&Regex>,
     epsilon: Option<f64>,
-    array_id_key: Option<&str>,
+    array_ids: ArrayIdKeys,
     ignore_whitespace: bool,
     ignore_case: bool,
+    check_key_order: bool,
 ) {
     // Use the existing array diff logic but with batching for very large arrays
     const BATCH_SIZE: usize = 10000;
@@ -1061,9 +5942,10 @@ fn memory_efficient_diff_arrays(
                 results,
                 ignore_keys_regex,
                 epsilon,
-                array_id_key,
+                array_ids,
                 ignore_whitespace,
                 ignore_case,
+                check_key_order,
             );
         }
     } else {
@@ -1075,9 +5957,10 @@ fn memory_efficient_diff_arrays(
             results,
             ignore_keys_regex,
             epsilon,
-            array_id_key,
+            array_ids,
             ignore_whitespace,
             ignore_case,
+            check_key_order,
         );
     }
 }
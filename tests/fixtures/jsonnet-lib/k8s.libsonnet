@@ -0,0 +1,18 @@
+{
+  deployment(name, env):: {
+    apiVersion: 'apps/v1',
+    kind: 'Deployment',
+    metadata: {
+      name: name,
+      labels: { app: name, env: env },
+    },
+    spec: {
+      replicas: if env == 'prod' then 3 else 1,
+      template: {
+        spec: {
+          containers: [{ name: name, image: 'registry.example.com/%s:1.4.2' % name }],
+        },
+      },
+    },
+  },
+}
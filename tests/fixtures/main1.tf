@@ -0,0 +1,18 @@
+variable "region" {
+  default = "us-east-1"
+}
+
+resource "aws_instance" "web" {
+  ami           = var.ami
+  instance_type = "t2.micro"
+  count         = 2
+  tags = {
+    Name = "web-${var.env}"
+  }
+  ingress {
+    port = 80
+  }
+  ingress {
+    port = 443
+  }
+}
@@ -0,0 +1,18 @@
+variable "region" {
+  default = "us-east-1"
+}
+
+resource "aws_instance" "web" {
+  ami           = data.aws_ami.ubuntu.id
+  instance_type = "t3.small"
+  count         = 2
+  tags = {
+    Name = "web-${var.env}"
+  }
+  ingress {
+    port = 80
+  }
+  ingress {
+    port = 8443
+  }
+}
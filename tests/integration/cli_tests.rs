@@ -52,6 +52,759 @@ fn test_basic_toml_diff() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_basic_json5_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/file1.json5")
+        .arg("../tests/fixtures/file2.json5");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ age: 30 -> 31"))
+        .stdout(predicate::str::contains(
+            "~ city: \"New York\" -> \"Boston\"",
+        ))
+        .stdout(predicate::str::contains("  + items[2]: \"orange\""));
+    Ok(())
+}
+
+#[test]
+fn test_basic_jsonc_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/file1.jsonc")
+        .arg("../tests/fixtures/file2.jsonc");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ age: 30 -> 31"))
+        .stdout(predicate::str::contains("  + items[2]: \"orange\""));
+    Ok(())
+}
+
+#[test]
+fn test_lenient_json_accepts_comments() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let file1 = dir.path().join("tsconfig.json");
+    let file2 = dir.path().join("tsconfig.new.json");
+    std::fs::write(&file1, "{\n  // strict mode\n  \"strict\": true,\n}\n")?;
+    std::fs::write(&file2, "{\n  \"strict\": false\n}\n")?;
+
+    diffx_cmd()
+        .arg(&file1)
+        .arg(&file2)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Failed to parse JSON"));
+
+    diffx_cmd()
+        .arg(&file1)
+        .arg(&file2)
+        .arg("--lenient-json")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ strict: true -> false"));
+    Ok(())
+}
+
+#[test]
+fn test_basic_hcl_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/main1.tf")
+        .arg("../tests/fixtures/main2.tf");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"~ resource.aws_instance.web.instance_type: "t2.micro" -> "t3.small""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#"~ resource.aws_instance.web.ami: "${var.ami}" -> "${data.aws_ami.ubuntu.id}""#,
+        ))
+        .stdout(predicate::str::contains(
+            "~ resource.aws_instance.web.ingress[1].port: 443 -> 8443",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_properties_against_yaml() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/application.properties")
+        .arg("../tests/fixtures/application.yaml")
+        .arg("--expand-dotted-keys")
+        .arg("--coerce-scalars");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"~ spring.datasource.url: "jdbc:postgresql://localhost/app" -> "jdbc:postgresql://db.internal/app""#,
+        ))
+        .stdout(predicate::str::contains(r#"~ app.hosts[1]: "beta" -> "gamma""#))
+        .stdout(predicate::str::contains("server").not())
+        .stdout(predicate::str::contains("greeting").not())
+        .stdout(predicate::str::contains("description").not());
+
+    // Without coercion every properties value is a string
+    diffx_cmd()
+        .arg("../tests/fixtures/application.properties")
+        .arg("../tests/fixtures/application.yaml")
+        .arg("--expand-dotted-keys")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"! server.port: "8080" (String) -> 8080 (Number)"#,
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_dotenv_inferred_from_file_name() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/.env.staging")
+        .arg("../tests/fixtures/.env.production");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"~ DATABASE_URL: "postgres://staging/app" -> "postgres://prod/app""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#"~ PRIVATE_KEY: "-----BEGIN-----\nline2\n-----END-----" -> "-----BEGIN-----\nline3\n-----END-----""#,
+        ))
+        .stdout(predicate::str::contains("API_KEY").not())
+        .stdout(predicate::str::contains("~ URL").not());
+    Ok(())
+}
+
+#[test]
+fn test_multi_document_yaml_by_position() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/k8s_bundle1.yaml")
+        .arg("../tests/fixtures/k8s_bundle2.yaml");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"~ [0].kind: "Deployment" -> "Service""#,
+        ))
+        .stdout(predicate::str::contains("+ [2]: "));
+    Ok(())
+}
+
+#[test]
+fn test_multi_document_yaml_by_identity() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/k8s_bundle1.yaml")
+        .arg("../tests/fixtures/k8s_bundle2.yaml")
+        .arg("--yaml-doc-key")
+        .arg("kind,metadata.namespace,metadata.name");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ [Deployment/default/web].spec.replicas: 2 -> 3",
+        ))
+        .stdout(predicate::str::contains(
+            "+ [ConfigMap/default/web-config]: ",
+        ))
+        .stdout(predicate::str::contains("Service").not());
+    Ok(())
+}
+
+#[test]
+fn test_yaml_anchors_resolved() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/ci_inline.yaml")
+        .arg("../tests/fixtures/ci_anchors.yaml");
+    cmd.assert().code(0).stdout("");
+    Ok(())
+}
+
+#[test]
+fn test_show_yaml_anchors() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/ci_anchors.yaml")
+        .arg("../tests/fixtures/ci_anchors_changed.yaml")
+        .arg("--show-yaml-anchors");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ build.image: \"rust:1.75\" -> \"rust:1.80\" (from &defaults)",
+        ))
+        .stdout(predicate::str::contains(
+            "~ test.timeout: 30 -> 60 (was from &defaults)",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_csv_key_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/products1.csv")
+        .arg("../tests/fixtures/products2.csv")
+        .arg("--csv-key")
+        .arg("id");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ [id=\"42\"].price: \"19.99\" -> \"17.99\"",
+        ))
+        .stdout(predicate::str::contains("+ [id=\"44\"]: "))
+        .stdout(predicate::str::contains("id=\"41\"").not());
+
+    // Key columns are array identity keys, so --array-id-key matches rows the same way
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/products1.csv")
+        .arg("../tests/fixtures/products2.csv")
+        .arg("--array-id-key")
+        .arg("id");
+    cmd.assert().code(1).stdout(predicate::str::contains(
+        "~ [id=\"42\"].price: \"19.99\" -> \"17.99\"",
+    ));
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/products1.csv")
+        .arg("../tests/fixtures/products2.csv")
+        .arg("--csv-key")
+        .arg("id")
+        .arg("--array-id-key")
+        .arg("sku");
+    cmd.assert().code(2).stderr(predicate::str::contains(
+        "--array-id-key and --csv-key name different keys",
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_tsv_inferred_from_extension() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/products1.tsv")
+        .arg("../tests/fixtures/products2.tsv");
+    cmd.assert().code(1).stdout(predicate::str::contains(
+        "~ [1].price: \"19.99\" -> \"17.99\"",
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_toml_datetimes_compared_as_datetimes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/release1.toml")
+        .arg("../tests/fixtures/release2.toml");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ release.build_date: 1979-05-27 -> 1979-05-28",
+        ))
+        .stdout(predicate::str::contains("published").not());
+    Ok(())
+}
+
+#[test]
+fn test_extension_values_in_json_output() -> Result<(), Box<dyn std::error::Error>> {
+    let output = diffx_cmd()
+        .arg("../tests/fixtures/release1.toml")
+        .arg("../tests/fixtures/release2.toml")
+        .arg("--output")
+        .arg("json")
+        .output()?;
+    let differences: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        differences,
+        serde_json::json!([{"Modified": ["release.build_date", "1979-05-27", "1979-05-28"]}])
+    );
+
+    // Input keys that look like marker keys are ordinary keys
+    let dir = tempfile::tempdir()?;
+    let file1 = dir.path().join("a.json");
+    let file2 = dir.path().join("b.json");
+    std::fs::write(&file1, r#"{"a": {"$__diffx_float": "nan"}}"#)?;
+    std::fs::write(&file2, r#"{"a": {"$__diffx_float": "inf"}}"#)?;
+    diffx_cmd()
+        .arg(&file1)
+        .arg(&file2)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"~ a.$__diffx_float: "nan" -> "inf""#,
+        ));
+    let output = diffx_cmd()
+        .arg(&file1)
+        .arg(&file2)
+        .arg("--output")
+        .arg("json")
+        .output()?;
+    let differences: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        differences,
+        serde_json::json!([{"Modified": ["a.$__diffx_float", "nan", "inf"]}])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_big_integers_compared_exactly() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/bigint1.json")
+        .arg("../tests/fixtures/bigint2.json");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ account_id: 12345678901234567890123 -> 12345678901234567890124",
+        ))
+        .stdout(predicate::str::contains("price").not());
+    Ok(())
+}
+
+#[test]
+fn test_yaml_tags_preserved() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/stack1.yaml")
+        .arg("../tests/fixtures/stack2.yaml");
+    cmd.assert().code(1).stdout(predicate::str::contains(
+        "~ Resources.Policy.Properties.Bucket: !Ref \"Bucket\" -> !Ref \"LogBucket\"",
+    ));
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/stack1.yaml")
+        .arg("../tests/fixtures/stack2.yaml")
+        .arg("--output")
+        .arg("yaml");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("- !Ref LogBucket"));
+    Ok(())
+}
+
+#[test]
+fn test_binary_format_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/users1.msgpack")
+        .arg("../tests/fixtures/users2.msgpack");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ age: 30 -> 31"))
+        .stdout(predicate::str::contains(
+            "~ avatar: base64:AQID -> base64:AQIE",
+        ));
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/orders1.bson")
+        .arg("../tests/fixtures/orders2.bson");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ [0].qty: 10 -> 12"));
+    Ok(())
+}
+
+#[test]
+fn test_binary_format_detected_from_content() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/record1.bin")
+        .arg("../tests/fixtures/record2.bin")
+        .arg("--bytes-as-hex");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ created: 2024-01-01T00:00:00Z -> 2024-02-01T00:00:00Z",
+        ))
+        .stdout(predicate::str::contains(
+            "~ checksum: hex:deadbeef -> hex:cafebabe",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_plist_xml_and_binary_diff() -> Result<(), Box<dyn std::error::Error>> {
+    // Info2.bin is the binary encoding of Info2.plist, recognised by its content
+    for new in ["Info2.plist", "Info2.bin"] {
+        let mut cmd = diffx_cmd();
+        cmd.arg("../tests/fixtures/Info1.plist")
+            .arg(format!("../tests/fixtures/{new}"));
+        cmd.assert()
+            .code(1)
+            .stdout(predicate::str::contains("~ CFBundleVersion: 41 -> 42"))
+            .stdout(predicate::str::contains(
+                "~ BuildDate: 2024-03-01T12:00:00Z -> 2024-03-08T12:00:00Z",
+            ))
+            .stdout(predicate::str::contains("PushToken").not());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_ron_diff_keeps_variant_names() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/game1.ron")
+        .arg("../tests/fixtures/game2.ron");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ GameConfig.window.WindowConfig.mode: \"Windowed\" -> \"Fullscreen\"",
+        ))
+        .stdout(predicate::str::contains(
+            "~ GameConfig.enemies[0].Patrol.speed: 2.5 -> 3.0",
+        ))
+        .stdout(predicate::str::contains(
+            "- GameConfig.enemies[1].Turret: {\"range\":30}",
+        ))
+        .stdout(predicate::str::contains(
+            "+ GameConfig.enemies[1].Sniper: {\"range\":30}",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_jsonnet_evaluated_before_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/deploy1.jsonnet")
+        .arg("../tests/fixtures/deploy1.rendered.json")
+        .arg("--jsonnet-jpath")
+        .arg("../tests/fixtures/jsonnet-lib")
+        .arg("--ext-str")
+        .arg("env=prod");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ metadata.labels.env: \"prod\" -> \"staging\"",
+        ))
+        .stdout(predicate::str::contains("~ spec.replicas: 3 -> 1"))
+        .stdout(predicate::str::contains("image").not());
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/deploy1.jsonnet")
+        .arg("../tests/fixtures/deploy1.rendered.json")
+        .arg("--jsonnet-jpath")
+        .arg("../tests/fixtures/jsonnet-lib")
+        .arg("--ext-str")
+        .arg("env=staging");
+    cmd.assert().code(0).stdout(predicate::str::is_empty());
+
+    // Evaluation errors name the file and line
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/deploy1.jsonnet")
+        .arg("../tests/fixtures/deploy1.rendered.json")
+        .arg("--jsonnet-jpath")
+        .arg("../tests/fixtures/jsonnet-lib");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to evaluate Jsonnet: external variable is not defined: env",
+        ))
+        .stderr(predicate::str::contains("deploy1.jsonnet:2:37-43"));
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/deploy1.jsonnet")
+        .arg("../tests/fixtures/deploy1.rendered.json")
+        .arg("--ext-str")
+        .arg("env=prod");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("can't resolve k8s.libsonnet from"))
+        .stderr(predicate::str::contains("tests/fixtures/deploy1.jsonnet"));
+    Ok(())
+}
+
+#[test]
+fn test_protobuf_diff_with_descriptor_set() -> Result<(), Box<dyn std::error::Error>> {
+    // order2.txtpb is the text format of order2.binpb without the unknown field 15
+    for (new, unknown_field) in [("order2.binpb", true), ("order2.txtpb", false)] {
+        let mut cmd = diffx_cmd();
+        cmd.arg("../tests/fixtures/order1.binpb")
+            .arg(format!("../tests/fixtures/{new}"))
+            .arg("--proto-descriptor")
+            .arg("../tests/fixtures/order.desc.pb")
+            .arg("--proto-message")
+            .arg("shop.v1.Order");
+        cmd.assert()
+            .code(1)
+            .stdout(predicate::str::contains(
+                "~ status: \"PENDING\" -> \"SHIPPED\"",
+            ))
+            .stdout(predicate::str::contains("~ items[1].qty: 1 -> 3"))
+            .stdout(predicate::str::contains("+ 15: 7").count(usize::from(unknown_field)));
+    }
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/order1.binpb")
+        .arg("../tests/fixtures/order2.binpb");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Protobuf input needs --proto-descriptor and --proto-message",
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_columnar_diff_by_key_columns() -> Result<(), Box<dyn std::error::Error>> {
+    for extension in ["parquet", "arrow"] {
+        let mut cmd = diffx_cmd();
+        cmd.arg(format!("../tests/fixtures/sales1.{extension}"))
+            .arg(format!("../tests/fixtures/sales2.{extension}"))
+            .arg("--key-columns")
+            .arg("id");
+        cmd.assert()
+            .code(1)
+            .stdout(predicate::str::contains("+ schema.stock: \"Int32\""))
+            .stdout(predicate::str::contains(
+                "~ rows[id=42].price: 19.99 -> 17.99",
+            ))
+            .stdout(predicate::str::contains("- rows[id=43]:"))
+            .stdout(predicate::str::contains("+ rows[id=45]:"))
+            .stdout(predicate::str::contains("rows[id=44].name").not());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_spreadsheet_diff_by_key_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/pricing1.xlsx")
+        .arg("../tests/fixtures/pricing2.xlsx")
+        .arg("--key-columns")
+        .arg("sku");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ Prices[sku=\"SKU-42\"].price: 19.99 -> 17.99",
+        ))
+        .stdout(predicate::str::contains("SKU-41").not());
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/pricing1.xlsx")
+        .arg("../tests/fixtures/pricing2.xlsx");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ Prices!B3: 19.99 -> 10.0"));
+    Ok(())
+}
+
+#[test]
+fn test_sqlite_schema_and_row_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/seed1.db")
+        .arg("../tests/fixtures/seed2.db");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ tables.users[id=7].email: \"ada@example.com\" -> \"ada@example.org\"",
+        ))
+        .stdout(predicate::str::contains("- tables.users[id=8]:"))
+        .stdout(predicate::str::contains(
+            "~ tables.settings[rowid=2].value: \"en\" -> \"de\"",
+        ))
+        .stdout(predicate::str::contains("+ schema.tables.audit_log:"))
+        .stdout(predicate::str::contains("~ schema.views.named_users:"));
+    Ok(())
+}
+
+#[test]
+fn test_sqlite_rejects_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/seed1.db")
+        .arg("-")
+        .arg("--format")
+        .arg("sqlite")
+        .stdin(std::process::Stdio::null());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "SQLite input cannot be read from stdin",
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_front_matter_directory_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/content1")
+        .arg("../tests/fixtures/content2")
+        .arg("--recursive");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ draft: true -> false"))
+        .stdout(predicate::str::contains("+ tags[2]: \"release\""))
+        .stdout(predicate::str::contains("~ weight: 10 -> 20"))
+        .stdout(predicate::str::contains("body").not());
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/content1/posts/hello-world.md")
+        .arg("../tests/fixtures/content2/posts/hello-world.md")
+        .arg("--front-matter-body");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ front_matter.draft: true -> false",
+        ))
+        .stdout(predicate::str::contains("~ body:"));
+    Ok(())
+}
+
+#[test]
+fn test_decode_embedded_strings() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("--decode-embedded")
+        .arg("../tests/fixtures/configmap1.yaml")
+        .arg("../tests/fixtures/configmap2.yaml");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ data[\"config.json\"].logging.level: \"info\" -> \"debug\"",
+        ))
+        .stdout(predicate::str::contains(
+            "+ data[\"config.json\"].features[1]: \"export\"",
+        ))
+        .stdout(predicate::str::contains(
+            "~ data[\"app.yaml\"].server.workers: 4 -> 8",
+        ));
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("--decode-embedded=^data\\[\"config\\.json\"\\]")
+        .arg("../tests/fixtures/configmap1.yaml")
+        .arg("../tests/fixtures/configmap2.yaml");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ data[\"config.json\"].logging.level: \"info\" -> \"debug\"",
+        ))
+        .stdout(predicate::str::contains("~ data[\"app.yaml\"]: \"server:"));
+
+    // Keys containing dots are bracketed, so a key `config` does not match them
+    let mut cmd = diffx_cmd();
+    cmd.arg("--decode-embedded=^data\\.config")
+        .arg("../tests/fixtures/configmap1.yaml")
+        .arg("../tests/fixtures/configmap2.yaml");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ data[\"config.json\"]: \"{"));
+    Ok(())
+}
+
+#[test]
+fn test_decode_base64_redacts_secrets() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/secret1.yaml")
+        .arg("../tests/fixtures/secret2.yaml")
+        .arg("--decode-base64=^data");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "~ data.password: (base64) \"<redacted sha256:",
+        ))
+        .stdout(predicate::str::contains("~ data[\"config.json\"].db.port:"))
+        .stdout(predicate::str::contains("~ data[\"logo.png\"]: \"iVBORw0K"))
+        .stdout(predicate::str::contains("~ data.token.payload.role:"))
+        .stdout(predicate::str::contains("hunter2").not())
+        .stdout(predicate::str::contains("username").not());
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/secret1.yaml")
+        .arg("../tests/fixtures/secret2.yaml")
+        .arg("--decode-base64=^data.*")
+        .arg("--show-secrets");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("<binary 24 bytes sha256:"))
+        .stdout(predicate::str::contains(
+            "~ data.password: (base64) \"hunter2\" -> (base64) \"correct-horse\"",
+        ))
+        .stdout(predicate::str::contains(
+            "~ data.token.payload.role: \"reader\" -> \"admin\"",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_compressed_inputs() -> Result<(), Box<dyn std::error::Error>> {
+    for (input1, input2) in [
+        ("nightly1.json.gz", "nightly2.yaml.zst"),
+        ("nightly1.json.bz2", "nightly2.yaml.xz"),
+    ] {
+        let mut cmd = diffx_cmd();
+        cmd.arg(format!("../tests/fixtures/{input1}"))
+            .arg(format!("../tests/fixtures/{input2}"));
+        cmd.assert()
+            .code(1)
+            .stdout(predicate::str::contains("~ replicas: 2 -> 3"))
+            .stdout(predicate::str::contains(
+                "~ logging.level: \"info\" -> \"debug\"",
+            ));
+    }
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("-")
+        .arg("../tests/fixtures/nightly1.json.bz2")
+        .stdin(std::fs::File::open("../tests/fixtures/nightly1.json.gz")?);
+    cmd.assert().success();
+
+    let dir1 = tempfile::tempdir()?;
+    let dir2 = tempfile::tempdir()?;
+    std::fs::copy(
+        "../tests/fixtures/nightly1.json.gz",
+        dir1.path().join("app.json.gz"),
+    )?;
+    std::fs::copy(
+        "../tests/fixtures/nightly1.json.bz2",
+        dir2.path().join("app.json.gz"),
+    )?;
+    let mut cmd = diffx_cmd();
+    cmd.arg(dir1.path()).arg(dir2.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("--- Comparing app.json.gz ---"));
+    Ok(())
+}
+
+#[test]
+fn test_format_sniffed_from_content() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let piped = dir.path().join("piped");
+    std::fs::write(&piped, "name: John\nage: 30\ncity: New York\n")?;
+    let mut cmd = diffx_cmd();
+    cmd.arg("-")
+        .arg("../tests/fixtures/file2.json")
+        .arg("--verbose")
+        .stdin(std::fs::File::open(&piped)?);
+    cmd.assert()
+        .code(1)
+        .stderr(predicate::str::contains("Input 1: yaml (content)"))
+        .stderr(predicate::str::contains("Input 2: json (file extension)"))
+        .stdout(predicate::str::contains("~ age: 30 -> 31"));
+
+    let extensionless = dir.path().join("settings");
+    std::fs::write(&extensionless, "[servers]\n")?;
+    let mut cmd = diffx_cmd();
+    cmd.arg(&extensionless).arg(&extensionless);
+    cmd.assert().code(2).stderr(predicate::str::contains(
+        "input could be YAML or TOML. Please specify --format.",
+    ));
+
+    // A known format on the other side does not settle ambiguous content
+    let mut cmd = diffx_cmd();
+    cmd.arg("-")
+        .arg("../tests/fixtures/file2.json")
+        .stdin(std::fs::File::open(&extensionless)?);
+    cmd.assert().code(2).stderr(predicate::str::contains(
+        "Could not infer the format of -: input could be YAML or TOML. Please specify --format.",
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_format_sniffed_per_file_in_directories() -> Result<(), Box<dyn std::error::Error>> {
+    let dir1 = tempfile::tempdir()?;
+    let dir2 = tempfile::tempdir()?;
+    std::fs::write(dir1.path().join("app"), "name: web\nreplicas: 2\n")?;
+    std::fs::write(dir2.path().join("app"), r#"{"name": "web", "replicas": 3}"#)?;
+    let mut cmd = diffx_cmd();
+    cmd.arg(dir1.path()).arg(dir2.path());
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("~ replicas: 2 -> 3"));
+
+    std::fs::write(dir1.path().join("settings"), "[servers]\n")?;
+    std::fs::write(dir2.path().join("settings"), "{}")?;
+    let mut cmd = diffx_cmd();
+    cmd.arg(dir1.path()).arg(dir2.path());
+    cmd.assert()
+        .code(2)
+        .stderr(predicate::str::contains(format!(
+            "Could not infer the format of {}: input could be YAML or TOML.",
+            dir1.path().join("settings").display()
+        )));
+    Ok(())
+}
+
 #[test]
 fn test_basic_ini_diff() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = diffx_cmd();
@@ -66,6 +819,19 @@ fn test_basic_ini_diff() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_ini_dialect_options() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/unit1.ini")
+        .arg("../tests/fixtures/unit2.ini")
+        .arg("--ini-case-sensitive")
+        .arg("--ini-duplicates-as-arrays");
+    cmd.assert().code(1).stdout(predicate::str::contains(
+        "~ Service.ExecStart[1]: \"/usr/bin/web-worker\" -> \"/usr/bin/web-worker --threads 4\"",
+    ));
+    Ok(())
+}
+
 #[test]
 fn test_basic_xml_diff() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = diffx_cmd();
@@ -74,9 +840,32 @@ fn test_basic_xml_diff() -> Result<(), Box<dyn std::error::Error>> {
     cmd.assert()
         .code(1)
         .stdout(predicate::str::contains(
-            "~ item.$text: \"value2\" -> \"value3\"",
+            "~ /root/item[2]/text(): \"value2\" -> \"new_value2\"",
         ))
-        .stdout(predicate::str::contains("~ item.@id: \"2\" -> \"3\""));
+        .stdout(predicate::str::contains(
+            "+ /root/item[3]: {\"@id\":\"3\",\"#text\":\"value3\"}",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_xml_arrays() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/catalog1.xml")
+        .arg("../tests/fixtures/catalog2.xml");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("+ /catalog/book[2]: "))
+        .stdout(predicate::str::contains("!").not());
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/catalog1.xml")
+        .arg("../tests/fixtures/catalog2.xml")
+        .arg("--xml-arrays");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains("+ /catalog/book[2]: "))
+        .stdout(predicate::str::contains("!").not());
     Ok(())
 }
 
@@ -297,10 +1086,10 @@ fn test_meta_chaining() -> Result<(), Box<dyn std::error::Error>> {
     cmd3.assert()
         .code(1)
         .stdout(predicate::str::contains(
-            r#"~ [1].Modified[1]: "1.0" -> "1.1""#,
+            r#"~ [0].Modified[1]: "1.0" -> "1.1""#,
         ))
         .stdout(predicate::str::contains(
-            r#"~ [1].Modified[2]: "1.1" -> "1.2""#,
+            r#"~ [0].Modified[2]: "1.1" -> "1.2""#,
         ))
         .stdout(predicate::str::contains(
             r#"+ [2]: {"Added":["features[2]","featureD"]}"#,
@@ -1309,6 +2098,39 @@ fn test_directory_comparison_with_recursive() -> Result<(), Box<dyn std::error::
     Ok(())
 }
 
+#[test]
+fn test_archive_comparison() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/bundle1.tgz")
+        .arg("../tests/fixtures/bundle2.zip")
+        .arg("--recursive");
+    cmd.assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "--- Comparing bundle/config/app.yaml ---",
+        ))
+        .stdout(predicate::str::contains("~ replicas: 2 -> 3"))
+        .stdout(predicate::str::contains(
+            "--- Only in ../tests/fixtures/bundle2.zip: bundle/config/cache.toml ---",
+        ));
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/bundle1.tgz")
+        .arg("../tests/fixtures/bundle2.zip");
+    cmd.assert().stdout(predicate::str::contains(
+        "Common subdirectories: ../tests/fixtures/bundle1.tgz/bundle and ../tests/fixtures/bundle2.zip/bundle",
+    ));
+
+    let mut cmd = diffx_cmd();
+    cmd.arg("../tests/fixtures/bundle2.zip")
+        .arg("../tests/fixtures/bundle2-unpacked")
+        .arg("--recursive");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "--- Comparing bundle/Chart.yaml ---",
+    ));
+    Ok(())
+}
+
 #[test]
 fn test_directory_vs_file_error() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = diffx_cmd();
@@ -1387,3 +2209,56 @@ fn test_non_recursive_does_not_compare_nested_files() -> Result<(), Box<dyn std:
         .stdout(predicate::str::contains("--- Comparing subdir/nested.json ---").not()); // Should NOT compare nested files
     Ok(())
 }
+
+#[test]
+fn test_check_key_order() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let file1 = dir.path().join("order1.yaml");
+    let file2 = dir.path().join("order2.yaml");
+    std::fs::write(&file1, "include:\n  first: a.conf\n  second: b.conf\n")?;
+    std::fs::write(&file2, "include:\n  second: b.conf\n  first: a.conf\n")?;
+
+    diffx_cmd().arg(&file1).arg(&file2).assert().code(0);
+
+    diffx_cmd()
+        .arg(&file1)
+        .arg(&file2)
+        .arg("--check-key-order")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"^ include: ["first","second"] -> ["second","first"]"#,
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_check_key_order_directory_mode() -> Result<(), Box<dyn std::error::Error>> {
+    let dir1 = tempfile::tempdir()?;
+    let dir2 = tempfile::tempdir()?;
+    std::fs::write(
+        dir1.path().join("nginx.yaml"),
+        "include:\n  first: a.conf\n  second: b.conf\n",
+    )?;
+    std::fs::write(
+        dir2.path().join("nginx.yaml"),
+        "include:\n  second: b.conf\n  first: a.conf\n",
+    )?;
+
+    diffx_cmd()
+        .arg(dir1.path())
+        .arg(dir2.path())
+        .assert()
+        .code(0);
+
+    diffx_cmd()
+        .arg(dir1.path())
+        .arg(dir2.path())
+        .arg("--check-key-order")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"^ include: ["first","second"] -> ["second","first"]"#,
+        ));
+    Ok(())
+}
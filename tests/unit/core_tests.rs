@@ -415,6 +415,68 @@ key1 = value1
     assert_eq!(parsed, expected);
 }
 
+#[test]
+fn test_parse_ini_with_options() {
+    let ini_content = r#"
+root = /var/www
+[Remote "origin"]
+url = https://example.com/repo.git#main
+fetch = +refs/heads/*
+fetch = +refs/tags/*
+Bare
+"#;
+    let options = IniOptions {
+        case_sensitive: true,
+        global_section: None,
+        duplicate_keys_as_arrays: true,
+        inline_comment_chars: Vec::new(),
+        allow_no_value: true,
+    };
+    let expected = json!({
+        "root": "/var/www",
+        "Remote \"origin\"": {
+            "url": "https://example.com/repo.git#main",
+            "fetch": ["+refs/heads/*", "+refs/tags/*"],
+            "Bare": null
+        }
+    });
+    assert_eq!(
+        parse_ini_with_options(ini_content, &options).unwrap(),
+        expected
+    );
+
+    let defaults = parse_ini(ini_content).unwrap();
+    assert_eq!(
+        defaults["remote \"origin\""]["url"],
+        json!("https://example.com/repo.git#main")
+    );
+    assert_eq!(
+        defaults["remote \"origin\""]["fetch"],
+        json!("+refs/tags/*")
+    );
+
+    let strict = IniOptions {
+        allow_no_value: false,
+        ..IniOptions::default()
+    };
+    assert!(parse_ini_with_options(ini_content, &strict).is_err());
+
+    let comments = parse_ini(
+        "[db]\nurl = http://h/#frag\npassword = a;b\nhost = db1 ; primary\nport = 5432 # default\nname = \"app # main\" # quoted\n",
+    )
+    .unwrap();
+    assert_eq!(
+        comments["db"],
+        json!({
+            "url": "http://h/#frag",
+            "password": "a;b",
+            "host": "db1",
+            "port": "5432",
+            "name": "\"app # main\""
+        })
+    );
+}
+
 #[test]
 fn test_parse_xml() {
     let xml_content = r#"
@@ -424,9 +486,11 @@ fn test_parse_xml() {
 </root>
 "#;
     let expected = json!({
-        "item": {
-            "$text": "value2",
-            "@id": "2"
+        "root": {
+            "item": [
+                {"@id": "1", "#text": "value1"},
+                {"@id": "2", "#text": "value2"}
+            ]
         }
     });
     let parsed = parse_xml(xml_content).unwrap();
@@ -441,14 +505,123 @@ fn test_parse_xml_single_element() {
 </data>
 "#;
     let expected = json!({
-        "name": {
-            "$text": "test"
+        "data": {
+            "name": "test"
         }
     });
     let parsed = parse_xml(xml_content).unwrap();
     assert_eq!(parsed, expected);
 }
 
+#[test]
+fn test_parse_xml_namespaces_and_mixed_content() {
+    let xml_content = r#"
+<project xmlns="urn:project" xmlns:m="urn:meta">
+    <m:owner team="core">Ops</m:owner>
+    <note>Hello <b>big</b> world</note>
+</project>
+"#;
+    let expected = json!({
+        "project": {
+            "@xmlns": "urn:project",
+            "{urn:meta}owner": {"@team": "core", "#text": "Ops"},
+            "note": {"$children": ["Hello", {"b": "big"}, "world"]}
+        }
+    });
+    assert_eq!(parse_xml(xml_content).unwrap(), expected);
+
+    // The prefix is not significant, only the namespace it is bound to
+    let renamed = xml_content
+        .replace("m:", "meta:")
+        .replace("xmlns:m=", "xmlns:meta=");
+    assert_eq!(parse_xml(&renamed).unwrap(), expected);
+
+    assert!(parse_xml("<a></a><b></b>").is_err());
+}
+
+#[test]
+fn test_parse_xml_document_order() {
+    // Text keeps its position among the child elements
+    let mixed = parse_xml("<a>x<b/>y</a>").unwrap();
+    assert_eq!(mixed, json!({"a": {"$children": ["x", {"b": ""}, "y"]}}));
+    assert_ne!(mixed, parse_xml("<a>xy<b/></a>").unwrap());
+
+    // Interleaved siblings are not grouped by name
+    let interleaved = parse_xml("<r><a>1</a><b>2</b><a>3</a></r>").unwrap();
+    assert_eq!(
+        interleaved,
+        json!({"r": {"$children": [{"a": "1"}, {"b": "2"}, {"a": "3"}]}})
+    );
+    let reordered = parse_xml("<r><a>1</a><a>3</a><b>2</b></r>").unwrap();
+    assert_eq!(reordered, json!({"r": {"a": ["1", "3"], "b": "2"}}));
+
+    let edited = parse_xml("<r><a>1</a><b>2</b><a>4</a></r>").unwrap();
+    let differences = diff(&interleaved, &edited, None, None, None);
+    assert_eq!(
+        xml_diff_paths(differences, &interleaved, &edited),
+        vec![DiffResult::Modified(
+            "/r/a[2]".to_string(),
+            json!("3"),
+            json!("4")
+        )]
+    );
+    assert_eq!(xml_path(&mixed, "a.$children[2]"), "/a/text()[2]");
+}
+
+#[test]
+fn test_align_xml_repeated_elements() {
+    let mut one = parse_xml("<list><item>a</item></list>").unwrap();
+    let mut two = parse_xml("<list><item>a</item><item>b</item></list>").unwrap();
+    align_xml_repeated_elements(&mut one, &mut two);
+    assert_eq!(one, json!({"list": {"item": ["a"]}}));
+
+    let differences = diff(&one, &two, None, None, None);
+    assert_eq!(
+        xml_diff_paths(differences, &one, &two),
+        vec![DiffResult::Added("/list/item[2]".to_string(), json!("b"))]
+    );
+}
+
+#[test]
+fn test_parse_xml_elements_as_arrays() {
+    let options = XmlOptions {
+        elements_as_arrays: true,
+    };
+    let one = parse_xml_with_options("<list><item>a</item></list>", &options).unwrap();
+    let two =
+        parse_xml_with_options("<list><item>a</item><item>b</item></list>", &options).unwrap();
+    assert_eq!(one, json!({"list": {"item": ["a"]}}));
+
+    let differences = diff(&one, &two, None, None, None);
+    assert_eq!(
+        xml_diff_paths(differences, &one, &two),
+        vec![DiffResult::Added("/list/item[2]".to_string(), json!("b"))]
+    );
+}
+
+#[test]
+fn test_xml_path() {
+    let document = json!({"catalog": {"book": [{"@id": "1", "#text": "a"}, {"@id": "2"}]}});
+    assert_eq!(
+        xml_path(&document, "catalog.book[1].@id"),
+        "/catalog/book[2]/@id"
+    );
+    assert_eq!(
+        xml_path(&document, "catalog.book[0].#text"),
+        "/catalog/book[1]/text()"
+    );
+    assert_eq!(
+        xml_path(&document, "catalog.book[@id=\"1\"].#text"),
+        "/catalog/book[@id=\"1\"]/text()"
+    );
+
+    let dotted = json!({"config": {"app.settings": {"@mode": "fast"}}});
+    assert_eq!(
+        xml_path(&dotted, r#"config["app.settings"].@mode"#),
+        "/config/app.settings/@mode"
+    );
+}
+
 #[test]
 fn test_parse_csv_with_headers() {
     let csv_content = "header1,header2\nvalueA,valueB\nvalueC,valueD";
@@ -469,3 +642,946 @@ fn test_parse_csv_no_headers() {
     let parsed = parse_csv(csv_content).unwrap();
     assert_eq!(parsed, expected);
 }
+
+#[test]
+fn test_parse_csv_with_options() {
+    let headerless = CsvOptions {
+        delimiter: b'\t',
+        has_headers: false,
+        ..CsvOptions::default()
+    };
+    let parsed = parse_csv_with_options("a\tb\nc\td", &headerless).unwrap();
+    assert_eq!(parsed, json!([["a", "b"], ["c", "d"]]));
+
+    let keyed = CsvOptions {
+        key_columns: vec!["id".to_string(), "region".to_string()],
+        ..CsvOptions::default()
+    };
+    let csv_content = "id,region,price\n42,eu,1.50\n42,us,2.00\n";
+    let parsed = parse_csv_with_options(csv_content, &keyed).unwrap();
+    assert_eq!(
+        parsed,
+        json!([
+            {"id": "42", "region": "eu", "price": "1.50"},
+            {"id": "42", "region": "us", "price": "2.00"}
+        ])
+    );
+
+    // Key columns match rows as a composite array identity key
+    let reordered = "id,region,price\n42,us,2.25\n42,eu,1.50\n";
+    let reordered = parse_csv_with_options(reordered, &keyed).unwrap();
+    let config = DiffConfig {
+        array_id_key: Some("id,region".to_string()),
+        ..DiffConfig::default()
+    };
+    assert_eq!(
+        diff_with_config(&parsed, &reordered, &config),
+        vec![DiffResult::Modified(
+            r#"[id="42",region="us"].price"#.to_string(),
+            json!("2.00"),
+            json!("2.25")
+        )]
+    );
+
+    let duplicate = "id,region,price\n42,eu,1.50\n42,eu,2.00\n";
+    let error = parse_csv_with_options(duplicate, &keyed).unwrap_err();
+    assert!(error.to_string().contains(r#"[id="42",region="eu"]"#));
+    let missing = CsvOptions {
+        key_columns: vec!["sku".to_string()],
+        ..CsvOptions::default()
+    };
+    assert!(parse_csv_with_options(csv_content, &missing).is_err());
+
+    let unquoted = CsvOptions {
+        quote: None,
+        ..CsvOptions::default()
+    };
+    let parsed = parse_csv_with_options("name\n\"quoted\"\n", &unquoted).unwrap();
+    assert_eq!(parsed, json!([{"name": "\"quoted\""}]));
+}
+
+#[test]
+fn test_diff_reports_changes_in_document_order() {
+    let v1: serde_json::Value = serde_json::from_str(r#"{"zeta": 1, "alpha": 2}"#).unwrap();
+    let v2: serde_json::Value =
+        serde_json::from_str(r#"{"zeta": 2, "middle": 0, "alpha": 3}"#).unwrap();
+    let differences = diff(&v1, &v2, None, None, None);
+    assert_eq!(
+        differences,
+        vec![
+            DiffResult::Modified("zeta".to_string(), json!(1), json!(2)),
+            DiffResult::Added("middle".to_string(), json!(0)),
+            DiffResult::Modified("alpha".to_string(), json!(2), json!(3)),
+        ]
+    );
+}
+
+#[test]
+fn test_diff_key_order_changed() {
+    let v1: serde_json::Value = serde_json::from_str(r#"{"tasks": {"b": 1, "a": 2}}"#).unwrap();
+    let v2: serde_json::Value = serde_json::from_str(r#"{"tasks": {"a": 2, "b": 1}}"#).unwrap();
+
+    assert!(diff(&v1, &v2, None, None, None).is_empty());
+
+    let config = DiffConfig {
+        check_key_order: true,
+        ..Default::default()
+    };
+    let differences = diff_with_config(&v1, &v2, &config);
+    assert_eq!(
+        differences,
+        vec![DiffResult::KeyOrderChanged(
+            "tasks".to_string(),
+            json!(["b", "a"]),
+            json!(["a", "b"])
+        )]
+    );
+}
+
+#[test]
+fn test_parse_json5() {
+    let json5_content = "{unquoted: 'single', hex: 0x10, list: [1, 2,], // comment\n}";
+    let expected = json!({"unquoted": "single", "hex": 16, "list": [1, 2]});
+    let parsed = parse_json5(json5_content).unwrap();
+    assert_eq!(parsed, expected);
+}
+
+#[test]
+fn test_parse_jsonc() {
+    let jsonc_content = r#"{
+    // line comment
+    "url": "http://example.com/*not-a-comment*/", /* block */
+    "paths": ["src",],
+}"#;
+    let expected = json!({"url": "http://example.com/*not-a-comment*/", "paths": ["src"]});
+    let parsed = parse_jsonc(jsonc_content).unwrap();
+    assert_eq!(parsed, expected);
+}
+
+#[test]
+fn test_parse_hcl() {
+    let hcl_content = r#"
+variable "region" {
+  default = "us-east-1"
+}
+
+resource "aws_instance" "web" {
+  ami   = var.ami
+  count = 2
+  ebs_block_device {
+    device_name = "/dev/sdb"
+  }
+  ebs_block_device {
+    device_name = "/dev/sdc"
+  }
+}
+"#;
+    let expected = json!({
+        "variable": {"region": {"default": "us-east-1"}},
+        "resource": {
+            "aws_instance": {
+                "web": {
+                    "ami": "${var.ami}",
+                    "count": 2,
+                    "ebs_block_device": [
+                        {"device_name": "/dev/sdb"},
+                        {"device_name": "/dev/sdc"}
+                    ]
+                }
+            }
+        }
+    });
+    let parsed = parse_hcl(hcl_content).unwrap();
+    assert_eq!(parsed, expected);
+}
+
+#[test]
+fn test_parse_properties() {
+    let properties_content = "# comment\n! another\nkey1=value1\nkey2 : value2\nkey3 value3\n\
+        multi=one \\\n    two\nescaped\\:key=caf\\u00e9\\tend\n";
+    let expected = json!({
+        "key1": "value1",
+        "key2": "value2",
+        "key3": "value3",
+        "multi": "one two",
+        "escaped:key": "caf\u{e9}\tend"
+    });
+    let parsed = parse_properties(properties_content).unwrap();
+    assert_eq!(parsed, expected);
+}
+
+#[test]
+fn test_parse_properties_expand_dotted_keys() {
+    let properties_content =
+        "spring.datasource.url=jdbc:h2:mem\nservers[0].host=a\nservers[1].host=b\n";
+    let options = PropertiesOptions {
+        expand_dotted_keys: true,
+        ..Default::default()
+    };
+    let expected = json!({
+        "spring": {"datasource": {"url": "jdbc:h2:mem"}},
+        "servers": [{"host": "a"}, {"host": "b"}]
+    });
+    let parsed = parse_properties_with_options(properties_content, &options).unwrap();
+    assert_eq!(parsed, expected);
+
+    assert!(parse_properties_with_options("a=1\na.b=2\n", &options).is_err());
+}
+
+#[test]
+fn test_parse_properties_coerce_scalars() {
+    let properties_content =
+        "port=8080\nratio=0.75\nenabled=TRUE\nmode=0755\npadded=8080 \nname=app\n";
+    let options = PropertiesOptions {
+        coerce_scalars: true,
+        ..Default::default()
+    };
+    let expected = json!({
+        "port": 8080,
+        "ratio": 0.75,
+        "enabled": true,
+        "mode": "0755",
+        "padded": "8080 ",
+        "name": "app"
+    });
+    let parsed = parse_properties_with_options(properties_content, &options).unwrap();
+    assert_eq!(parsed, expected);
+
+    let yaml = parse_yaml("port: 8080\nratio: 0.75\nenabled: true\n").unwrap();
+    let properties =
+        parse_properties_with_options("port=8080\nratio=0.75\nenabled=true\n", &options).unwrap();
+    assert!(diff(&yaml, &properties, None, None, None).is_empty());
+}
+
+#[test]
+fn test_parse_dotenv() {
+    let dotenv_content = r#"# comment
+export HOST=localhost
+PORT = 5432 # inline comment
+LITERAL='no $EXPANSION \n here'
+ESCAPED="tab\there \"quoted\""
+MULTI="first
+second"
+REF=${HOME}/app
+EMPTY=
+"#;
+    let expected = json!({
+        "HOST": "localhost",
+        "PORT": "5432",
+        "LITERAL": "no $EXPANSION \\n here",
+        "ESCAPED": "tab\there \"quoted\"",
+        "MULTI": "first\nsecond",
+        "REF": "${HOME}/app",
+        "EMPTY": ""
+    });
+    let parsed = parse_dotenv(dotenv_content).unwrap();
+    assert_eq!(parsed, expected);
+
+    assert!(parse_dotenv("UNTERMINATED=\"oops\n").is_err());
+}
+
+#[test]
+fn test_parse_yaml_documents() {
+    let yaml_content = "a: 1\n---\nb: 2\n---\n";
+    let documents = parse_yaml_documents(yaml_content).unwrap();
+    assert_eq!(documents, vec![json!({"a": 1}), json!({"b": 2})]);
+
+    assert_eq!(parse_yaml("a: 1\n").unwrap(), json!({"a": 1}));
+    assert_eq!(
+        parse_yaml(yaml_content).unwrap(),
+        json!([{"a": 1}, {"b": 2}])
+    );
+}
+
+#[test]
+fn test_diff_documents_by_identity() {
+    let docs1 = vec![
+        json!({"kind": "Deployment", "metadata": {"name": "web"}, "spec": {"replicas": 2}}),
+        json!({"kind": "Service", "metadata": {"name": "web"}}),
+    ];
+    let docs2 = vec![
+        json!({"kind": "Service", "metadata": {"name": "web"}}),
+        json!({"kind": "Deployment", "metadata": {"name": "web"}, "spec": {"replicas": 3}}),
+    ];
+    let keys = vec!["kind".to_string(), "metadata.name".to_string()];
+
+    let differences = diff_documents(&docs1, &docs2, Some(&keys), &DiffConfig::default());
+    assert_eq!(
+        differences,
+        vec![DiffResult::Modified(
+            "[Deployment/web].spec.replicas".to_string(),
+            json!(2),
+            json!(3)
+        )]
+    );
+
+    let removed = diff_documents(&docs1, &docs2[..1], Some(&keys), &DiffConfig::default());
+    assert_eq!(
+        removed,
+        vec![DiffResult::Removed(
+            "[Deployment/web]".to_string(),
+            docs1[0].clone()
+        )]
+    );
+}
+
+#[test]
+fn test_parse_yaml_merge_keys() {
+    let yaml_content = r#"
+base: &base
+  image: rust
+  timeout: 30
+extended: &extended
+  <<: *base
+  retries: 2
+job:
+  <<: [*extended, {image: alpine, shell: sh}]
+  timeout: 60
+"#;
+    let parsed = parse_yaml(yaml_content).unwrap();
+    assert_eq!(
+        parsed["job"],
+        json!({"image": "rust", "timeout": 60, "retries": 2, "shell": "sh"})
+    );
+    let job_keys: Vec<&String> = parsed["job"].as_object().unwrap().keys().collect();
+    assert_eq!(job_keys, vec!["image", "retries", "shell", "timeout"]);
+
+    assert!(parse_yaml("a: 1\n<<: 5\n").is_err());
+
+    let cycle = parse_yaml("a: &a\n  b: *a\n").unwrap_err();
+    assert!(cycle.to_string().contains("Alias cycle in YAML at line 2"));
+
+    let bomb = r#"
+a: &a [x, x, x, x, x, x, x, x, x]
+b: &b [*a, *a, *a, *a, *a, *a, *a, *a, *a]
+c: &c [*b, *b, *b, *b, *b, *b, *b, *b, *b]
+d: &d [*c, *c, *c, *c, *c, *c, *c, *c, *c]
+e: [*d, *d, *d, *d, *d, *d, *d, *d, *d]
+"#;
+    let bomb = parse_yaml(bomb).unwrap_err();
+    assert!(bomb.to_string().contains("possible alias bomb"));
+    // The same aliases one level shallower expand within the limit
+    let shared = "a: &a [x, x, x]\nb: &b [*a, *a, *a]\nc: [*b, *b, *b]\n";
+    assert_eq!(
+        parse_yaml(shared).unwrap()["c"][2][1],
+        json!(["x", "x", "x"])
+    );
+}
+
+#[test]
+fn test_yaml_anchor_sources() {
+    let yaml_content = r#"
+defaults: &defaults
+  image: rust
+env: &env
+  RUST_LOG: info
+job:
+  <<: *defaults
+  env: *env
+  script: cargo test
+"#;
+    let sources = yaml_anchor_sources(yaml_content).unwrap();
+    assert_eq!(sources.len(), 1);
+    let sources = &sources[0];
+
+    assert_eq!(anchor_for_path(sources, "job.image"), Some("defaults"));
+    assert_eq!(anchor_for_path(sources, "job.env.RUST_LOG"), Some("env"));
+    assert_eq!(anchor_for_path(sources, "defaults.image"), Some("defaults"));
+    assert_eq!(anchor_for_path(sources, "job.script"), None);
+}
+
+#[test]
+fn test_extension_values() {
+    let toml_content = "at = 1979-05-27T07:32:00Z\nratio = nan\n";
+    let parsed = parse_toml(toml_content).unwrap();
+    assert_eq!(
+        extension_value(&parsed["at"]),
+        Some(ExtensionValue::DateTime("1979-05-27T07:32:00Z"))
+    );
+    assert_eq!(format_value(&parsed["ratio"]), "nan");
+    assert_eq!(value_type_name(&parsed["at"]), "DateTime");
+
+    let shifted = parse_toml("at = 1979-05-27T00:32:00-07:00\nratio = nan\n").unwrap();
+    assert!(diff(&parsed, &shifted, None, None, None).is_empty());
+
+    let yaml = parse_yaml("bucket: !Ref Logs\nlimit: .inf\n").unwrap();
+    assert_eq!(yaml["bucket"], tagged_value("!Ref", json!("Logs")));
+    assert_eq!(yaml["limit"], float_value(f64::INFINITY));
+    assert_eq!(format_value(&yaml["bucket"]), "!Ref \"Logs\"");
+
+    let retagged = parse_yaml("bucket: !GetAtt Logs\nlimit: .inf\n").unwrap();
+    assert_eq!(
+        diff(&yaml, &retagged, None, None, None),
+        vec![DiffResult::Modified(
+            "bucket".to_string(),
+            tagged_value("!Ref", json!("Logs")),
+            tagged_value("!GetAtt", json!("Logs"))
+        )]
+    );
+}
+
+#[test]
+fn test_reserved_keys_round_trip() {
+    let v1 = parse_json(r#"{"a": {"$__diffx_float": "nan"}, "b": {"$$__diffx_x": 1}}"#).unwrap();
+    let v2 = parse_json(r#"{"a": {"$__diffx_float": "inf"}, "b": {"$$__diffx_x": 1}}"#).unwrap();
+    assert_eq!(extension_value(&v1["a"]), None);
+    assert_eq!(
+        diff(&v1, &v2, None, None, None),
+        vec![DiffResult::Modified(
+            "a.$__diffx_float".to_string(),
+            json!("nan"),
+            json!("inf")
+        )]
+    );
+    assert_eq!(
+        to_plain_json(&v1),
+        json!({"a": {"$__diffx_float": "nan"}, "b": {"$$__diffx_x": 1}})
+    );
+    assert_eq!(format_value(&v1["a"]), r#"{"$__diffx_float":"nan"}"#);
+
+    // The same key next to real extension values
+    let toml = parse_toml("\"$__diffx_datetime\" = \"x\"\nat = 1979-05-27\n").unwrap();
+    assert_eq!(
+        to_plain_json(&toml),
+        json!({"$__diffx_datetime": "x", "at": "1979-05-27"})
+    );
+}
+
+#[test]
+fn test_evaluate_jsonnet() {
+    let path = std::path::Path::new("../tests/fixtures/deploy1.jsonnet");
+    let options = JsonnetOptions {
+        library_paths: vec!["../tests/fixtures/jsonnet-lib".into()],
+        ext_strs: vec![("env".to_string(), "staging".to_string())],
+    };
+    let rendered = evaluate_jsonnet(path, &options).unwrap();
+    let expected =
+        parse_json(&std::fs::read_to_string("../tests/fixtures/deploy1.rendered.json").unwrap())
+            .unwrap();
+    assert_eq!(rendered, expected);
+
+    let error = evaluate_jsonnet(path, &JsonnetOptions::default()).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("can't resolve k8s.libsonnet from"));
+}
+
+#[test]
+fn test_number_comparison_is_exact() {
+    let v1: serde_json::Value =
+        serde_json::from_str(r#"{"big": 12345678901234567890123, "price": 1.50}"#).unwrap();
+    let v2: serde_json::Value =
+        serde_json::from_str(r#"{"big": 12345678901234567890124, "price": 1.5}"#).unwrap();
+    let differences = diff(&v1, &v2, None, None, None);
+    assert_eq!(differences.len(), 1);
+    assert!(matches!(&differences[0], DiffResult::Modified(path, _, _) if path == "big"));
+}
+
+#[test]
+fn test_parse_binary_formats() {
+    // {"id": 7, "blob": b"\x01\x02"} in MessagePack and CBOR
+    let msgpack = [
+        0x82, 0xa2, b'i', b'd', 0x07, 0xa4, b'b', b'l', b'o', b'b', 0xc4, 0x02, 1, 2,
+    ];
+    let cbor = [
+        0xa2, 0x62, b'i', b'd', 0x07, 0x64, b'b', b'l', b'o', b'b', 0x42, 1, 2,
+    ];
+    let expected = json!({"id": 7, "blob": bytes_value(&[1, 2], false)});
+    assert_eq!(parse_msgpack(&msgpack).unwrap(), expected);
+    assert_eq!(parse_cbor(&cbor).unwrap(), expected);
+    assert_eq!(format_value(&expected["blob"]), "base64:AQI=");
+
+    let hex = parse_cbor_with_options(&cbor, &BinaryOptions { bytes_as_hex: true }).unwrap();
+    assert_eq!(format_value(&hex["blob"]), "hex:0102");
+
+    assert_eq!(
+        detect_binary_format(&msgpack),
+        Some(BinaryFormat::MessagePack)
+    );
+    assert_eq!(detect_binary_format(&cbor), Some(BinaryFormat::Cbor));
+    assert_eq!(detect_binary_format(b"{\"id\": 7}"), None);
+    assert!(parse_msgpack(&msgpack[..8]).is_err());
+
+    // MessagePack timestamp extension (type -1, 32-bit seconds)
+    let timestamp = [0xd6, 0xff, 0x65, 0x92, 0x00, 0x80];
+    assert_eq!(
+        parse_msgpack(&timestamp).unwrap(),
+        datetime_value("2024-01-01T00:00:00Z")
+    );
+}
+
+#[test]
+fn test_parse_bson_stream() {
+    let document = |id: i32| {
+        let mut body = vec![0x10];
+        body.extend_from_slice(b"id\0");
+        body.extend_from_slice(&id.to_le_bytes());
+        body.push(0x07);
+        body.extend_from_slice(b"_id\0");
+        body.extend_from_slice(&[
+            0x65, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6, 0x07, 0x18, 0x29, 0x3a, 0x4b,
+        ]);
+        let mut bytes = ((body.len() + 5) as i32).to_le_bytes().to_vec();
+        bytes.extend(body);
+        bytes.push(0);
+        bytes
+    };
+
+    let single = document(1);
+    assert_eq!(detect_binary_format(&single), Some(BinaryFormat::Bson));
+    assert_eq!(
+        parse_bson(&single).unwrap(),
+        json!({"id": 1, "_id": tagged_value("ObjectId", json!("65a1b2c3d4e5f60718293a4b"))})
+    );
+
+    let stream = [document(1), document(2)].concat();
+    let documents = parse_bson_documents(&stream).unwrap();
+    assert_eq!(documents.len(), 2);
+    assert_eq!(documents[1]["id"], json!(2));
+    assert!(parse_bson(&stream[..stream.len() - 1]).is_err());
+}
+
+#[test]
+fn test_parse_plist() {
+    let plist = br#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>Version</key>
+    <integer>3</integer>
+    <key>Built</key>
+    <date>2024-03-01T12:00:00Z</date>
+    <key>Token</key>
+    <data>AQI=</data>
+</dict>
+</plist>"#;
+    let parsed = parse_plist(plist).unwrap();
+    assert_eq!(
+        parsed,
+        json!({
+            "Version": 3,
+            "Built": datetime_value("2024-03-01T12:00:00Z"),
+            "Token": bytes_value(&[1, 2], false),
+        })
+    );
+    assert_eq!(detect_binary_format(plist), None);
+    assert_eq!(
+        detect_binary_format(b"bplist00\xd0\x08\x00\x00"),
+        Some(BinaryFormat::Plist)
+    );
+}
+
+#[test]
+fn test_parse_ron() {
+    let ron = r##"
+// comment
+Config(
+    name: "demo",
+    mode: Windowed,
+    size: (1280, 720),
+    color: Rgb(255, 0, 0x10),
+    spawn: Some(Point(x: 0.5, y: -1)),
+    fallback: None,
+    tags: {"a": 'x'},
+    raw: r#"say "hi""#,
+)
+"##;
+    assert_eq!(
+        parse_ron(ron).unwrap(),
+        json!({
+            "Config": {
+                "name": "demo",
+                "mode": "Windowed",
+                "size": [1280, 720],
+                "color": {"Rgb": [255, 0, 16]},
+                "spawn": {"Point": {"x": 0.5, "y": -1}},
+                "fallback": null,
+                "tags": {"a": "x"},
+                "raw": "say \"hi\"",
+            }
+        })
+    );
+
+    let err = parse_ron("Config(name: \"demo\"\n  mode: Windowed)").unwrap_err();
+    assert!(err.to_string().contains("line 2"), "{err}");
+}
+
+#[test]
+fn test_parse_protobuf() {
+    let options = ProtobufOptions {
+        descriptor_set: include_bytes!("../fixtures/order.desc.pb").to_vec(),
+        message: "shop.v1.Order".to_string(),
+        bytes_as_hex: true,
+        encoding: ProtobufEncoding::Auto,
+    };
+    let binary = parse_protobuf(include_bytes!("../fixtures/order2.binpb"), &options).unwrap();
+    assert_eq!(binary["status"], json!("SHIPPED"));
+    assert_eq!(binary["labels"], json!({"channel": "web"}));
+    assert_eq!(
+        binary["checksum"],
+        bytes_value(&[0xde, 0xad, 0xbe, 0xef], true)
+    );
+    // Field 15 is not in the descriptor
+    assert_eq!(binary["15"], json!(7));
+
+    let text = parse_protobuf(include_bytes!("../fixtures/order2.txtpb"), &options).unwrap();
+    let differences = diff(&binary, &text, None, None, None);
+    assert_eq!(
+        differences,
+        vec![DiffResult::Removed("15".to_string(), json!(7))]
+    );
+
+    // Field 1 (tag '\n') with a 32-byte (' ') id: binary, though every byte is printable
+    let printable = b"\n ord-1001-0000-0000-0000-00000000";
+    assert!(std::str::from_utf8(printable).is_ok());
+    let parsed = parse_protobuf(printable, &options).unwrap();
+    assert_eq!(parsed, json!({"id": "ord-1001-0000-0000-0000-00000000"}));
+    let text_options = ProtobufOptions {
+        encoding: ProtobufEncoding::Text,
+        ..options.clone()
+    };
+    assert!(parse_protobuf(printable, &text_options).is_err());
+
+    let unknown_type = ProtobufOptions {
+        message: "shop.v1.Missing".to_string(),
+        ..options
+    };
+    assert!(parse_protobuf(b"", &unknown_type).is_err());
+}
+
+#[test]
+fn test_parse_columnar_files() {
+    let parquet = include_bytes!("../fixtures/sales1.parquet");
+    let arrow = include_bytes!("../fixtures/sales1.arrow");
+    assert_eq!(detect_binary_format(parquet), Some(BinaryFormat::Parquet));
+    assert_eq!(detect_binary_format(arrow), Some(BinaryFormat::ArrowIpc));
+
+    let options = ColumnarOptions {
+        key_columns: vec!["id".to_string()],
+        batch_size: 2,
+        ..ColumnarOptions::default()
+    };
+    let file = std::fs::File::open("../tests/fixtures/sales1.parquet").unwrap();
+    let parsed = parse_parquet(file, &options).unwrap();
+    assert_eq!(
+        parsed["schema"],
+        json!({"id": "Int64", "name": "Utf8", "price": "Float64", "listed": "Date32"})
+    );
+    assert_eq!(
+        parsed["rows"][1],
+        json!({"id": 43, "name": "Gadget", "price": 5.0, "listed": datetime_value("2024-01-01")})
+    );
+    let file = std::fs::File::open("../tests/fixtures/sales1.arrow").unwrap();
+    assert_eq!(parse_arrow_ipc(file, &options).unwrap(), parsed);
+    let in_memory = std::io::Cursor::new(arrow.as_slice());
+    assert_eq!(parse_arrow_ipc(in_memory, &options).unwrap(), parsed);
+
+    let in_memory = bytes::Bytes::from_static(parquet);
+    let by_position = parse_parquet(in_memory, &ColumnarOptions::default()).unwrap();
+    assert_eq!(by_position["rows"].as_array().map(Vec::len), Some(3));
+}
+
+#[test]
+fn test_diff_columnar() {
+    let open = |name: &str| std::fs::File::open(format!("../tests/fixtures/{name}")).unwrap();
+    let options = ColumnarOptions {
+        key_columns: vec!["id".to_string()],
+        batch_size: 1,
+        ..ColumnarOptions::default()
+    };
+
+    // Reading the rows side by side finds what diffing the loaded files finds
+    for array_id_key in [None, Some("id".to_string())] {
+        let config = DiffConfig {
+            array_id_key,
+            ..DiffConfig::default()
+        };
+        let loaded = diff_with_config(
+            &parse_parquet(open("sales1.parquet"), &options).unwrap(),
+            &parse_arrow_ipc(open("sales2.arrow"), &options).unwrap(),
+            &config,
+        );
+        let streamed = diff_columnar(
+            read_parquet(open("sales1.parquet"), &options).unwrap(),
+            read_arrow_ipc(open("sales2.arrow"), &options).unwrap(),
+            &config,
+        )
+        .unwrap();
+        assert!(!streamed.is_empty());
+        assert_eq!(streamed, loaded);
+    }
+
+    let rows = read_parquet(open("sales1.parquet"), &options).unwrap();
+    assert_eq!(rows.schema()["price"], json!("Float64"));
+    assert_eq!(rows.count(), 3);
+}
+
+#[test]
+fn test_parse_spreadsheet() {
+    let workbook = include_bytes!("../fixtures/pricing1.xlsx");
+
+    let cells = parse_spreadsheet(workbook, &SpreadsheetOptions::default()).unwrap();
+    assert_eq!(cells["Prices!A1"], json!("sku"));
+    assert_eq!(cells["Prices!B3"], json!(19.99));
+
+    let options = SpreadsheetOptions {
+        table: CsvOptions {
+            key_columns: vec!["sku".to_string()],
+            ..CsvOptions::default()
+        },
+        ..SpreadsheetOptions::default()
+    };
+    let records = parse_spreadsheet(workbook, &options).unwrap();
+    assert_eq!(records["Prices"][1]["sku"], json!("SKU-42"));
+    assert_eq!(records["Prices"][1]["price"], json!(19.99));
+    assert_eq!(records["Prices"][1]["total"], json!(59.97));
+
+    let formulas = SpreadsheetOptions {
+        compare_formulas: true,
+        ..options
+    };
+    let records = parse_spreadsheet(workbook, &formulas).unwrap();
+    assert_eq!(records["Prices"][1]["total"], json!("=B3*C3"));
+}
+
+#[test]
+fn test_parse_sqlite() {
+    let path = std::path::Path::new("../tests/fixtures/seed1.db");
+    assert_eq!(
+        detect_binary_format(&std::fs::read(path).unwrap()),
+        Some(BinaryFormat::Sqlite)
+    );
+
+    let parsed = parse_sqlite(path, &BinaryOptions::default()).unwrap();
+    assert_eq!(
+        parsed["schema"]["tables"]["users"]["columns"]["email"],
+        json!({"type": "TEXT", "not_null": true, "default": null, "primary_key": false})
+    );
+    assert_eq!(
+        parsed["schema"]["indexes"]["idx_users_email"],
+        json!("CREATE INDEX idx_users_email ON users(email)")
+    );
+    assert_eq!(
+        parsed["tables"]["users"][0],
+        json!({"id": 7, "email": "ada@example.com", "name": "Ada", "avatar": bytes_value(b"\x89PNG", false)})
+    );
+    assert_eq!(parsed["tables"]["settings"][0]["rowid"], json!(1));
+    assert_eq!(parsed["tables"]["settings"][0]["value"], json!("dark"));
+
+    let row_keys = sqlite_row_keys(&parsed);
+    assert_eq!(row_keys["tables.users"], vec!["id"]);
+    assert_eq!(row_keys["tables.settings"], vec!["rowid"]);
+    assert_eq!(row_keys["tables.plan_features"], vec!["plan", "feature"]);
+}
+
+#[test]
+fn test_parse_front_matter() {
+    let options = FrontMatterOptions::default();
+    let yaml = "---\ntitle: Hello\ntags: [a, b]\n...\n# Hello\n";
+    assert_eq!(
+        parse_front_matter(yaml, &options).unwrap(),
+        json!({"title": "Hello", "tags": ["a", "b"]})
+    );
+
+    let toml = "+++\ntitle = \"About\"\nweight = 10\n+++\nBody\n";
+    assert_eq!(
+        parse_front_matter(toml, &options).unwrap(),
+        json!({"title": "About", "weight": 10})
+    );
+
+    let with_body = FrontMatterOptions { include_body: true };
+    assert_eq!(
+        parse_front_matter(toml, &with_body).unwrap(),
+        json!({"front_matter": {"title": "About", "weight": 10}, "body": "Body\n"})
+    );
+    assert_eq!(
+        parse_front_matter("# No metadata\n", &with_body).unwrap(),
+        json!({"front_matter": {}, "body": "# No metadata\n"})
+    );
+    assert!(parse_front_matter("---\ntitle: Open\n", &options).is_err());
+}
+
+#[test]
+fn test_decode_embedded_strings() {
+    let v1 = json!({
+        "data": {
+            "config.json": "{\"logging\": {\"level\": \"info\"}}",
+            "app.yaml": "server:\n  port: 8080\n",
+            "motd": "Welcome",
+            "list": "[1, 2]"
+        }
+    });
+    let v2 = json!({
+        "data": {
+            "config.json": "{\"logging\": {\"level\": \"debug\"}}",
+            "app.yaml": "server:\n  port: 9090\n",
+            "motd": "Welcome",
+            "list": "[1, 2]"
+        }
+    });
+
+    let everywhere = regex::Regex::new("").unwrap();
+    let decoded = decode_embedded_strings(&v1, &everywhere);
+    assert_eq!(
+        decoded["data"]["config.json"],
+        encoded_value("json", json!({"logging": {"level": "info"}}))
+    );
+    assert_eq!(
+        decoded["data"]["app.yaml"],
+        encoded_value("yaml", json!({"server": {"port": 8080}}))
+    );
+    assert_eq!(decoded["data"]["motd"], json!("Welcome"));
+
+    let config = DiffConfig {
+        decode_embedded: Some(everywhere),
+        ..Default::default()
+    };
+    assert_eq!(
+        diff_with_config(&v1, &v2, &config),
+        vec![
+            DiffResult::Modified(
+                r#"data["config.json"].logging.level"#.to_string(),
+                json!("info"),
+                json!("debug")
+            ),
+            DiffResult::Modified(
+                r#"data["app.yaml"].server.port"#.to_string(),
+                json!(8080),
+                json!(9090)
+            ),
+        ]
+    );
+
+    let config_only = DiffConfig {
+        decode_embedded: Some(regex::Regex::new(r#"\.json"\]$"#).unwrap()),
+        ..Default::default()
+    };
+    let differences = diff_with_config(&v1, &v2, &config_only);
+    assert_eq!(differences.len(), 2);
+    assert!(
+        matches!(&differences[1], DiffResult::Modified(path, _, _) if path == r#"data["app.yaml"]"#)
+    );
+}
+
+#[test]
+fn test_decode_base64_strings() {
+    let v1 = json!({"data": {"password": "aHVudGVyMg==", "config.json": "eyJwb3J0IjogNTQzMn0=", "kind": "Opaque"}});
+    let v2 = json!({"data": {"password": "Y29ycmVjdC1ob3JzZQ==", "config.json": "eyJwb3J0IjogNjQzMn0=", "kind": "Opaque"}});
+    let paths = regex::Regex::new(r#"^data(\.password|\["config\.json"\])"#).unwrap();
+
+    let revealed = decode_base64_strings(&v1, &paths, true);
+    assert_eq!(
+        revealed["data"]["password"],
+        encoded_value("base64", json!("hunter2"))
+    );
+    assert_eq!(
+        revealed["data"]["config.json"],
+        encoded_value("base64", encoded_value("json", json!({"port": 5432})))
+    );
+    assert_eq!(revealed["data"]["kind"], json!("Opaque"));
+
+    let binary = decode_base64_strings(
+        &json!("iVBORw0KGgo="),
+        &regex::Regex::new("").unwrap(),
+        true,
+    );
+    assert_eq!(
+        binary,
+        encoded_value("base64", json!("<binary 8 bytes sha256:4c4b6a3be131>"))
+    );
+
+    // Words that happen to be base64 are left alone
+    let words = json!({"env": {"name": "prod", "branch": "main", "code": "password"}});
+    let prefix = regex::Regex::new(r"^env\.").unwrap();
+    assert_eq!(decode_base64_strings(&words, &prefix, true), words);
+    let whole = regex::Regex::new(r"^env\.code$").unwrap();
+    let decoded = decode_base64_strings(&words, &whole, true);
+    assert!(decoded["env"]["code"]
+        .to_string()
+        .contains("<binary 6 bytes"));
+    let padded = decode_base64_strings(&json!("aHVudGVyMg="), &whole, true);
+    assert_eq!(padded, json!("aHVudGVyMg="));
+
+    let config = DiffConfig {
+        decode_base64: Some(paths),
+        ..Default::default()
+    };
+    let differences = diff_with_config(&v1, &v2, &config);
+    assert_eq!(differences.len(), 2);
+    for difference in &differences {
+        let rendered = format!("{difference:?}");
+        assert!(!rendered.contains("hunter2") && !rendered.contains("5432"));
+        assert!(rendered.contains("<redacted sha256:"));
+    }
+    assert!(
+        matches!(&differences[1], DiffResult::Modified(path, _, _) if path == r#"data["config.json"].port"#)
+    );
+
+    let shown = DiffConfig {
+        show_secrets: true,
+        ..config
+    };
+    assert_eq!(
+        diff_with_config(&v1, &v2, &shown)[1],
+        DiffResult::Modified(
+            r#"data["config.json"].port"#.to_string(),
+            json!(5432),
+            json!(6432)
+        )
+    );
+}
+
+#[test]
+fn test_read_decompressed() {
+    let expected =
+        "{\n  \"service\": \"web\",\n  \"replicas\": 2,\n  \"logging\": {\"level\": \"info\"}\n}\n";
+    let gzip = include_bytes!("../fixtures/nightly1.json.gz");
+    let bzip2 = include_bytes!("../fixtures/nightly1.json.bz2");
+    for (compressed, compression) in [
+        (&gzip[..], Compression::Gzip),
+        (&bzip2[..], Compression::Bzip2),
+    ] {
+        let (content, detected) = read_decompressed(compressed, None).unwrap();
+        assert_eq!(String::from_utf8(content).unwrap(), expected);
+        assert_eq!(detected, Some(compression));
+    }
+
+    let (content, detected) = read_decompressed(&b"{}"[..], None).unwrap();
+    assert_eq!((content.as_slice(), detected), (&b"{}"[..], None));
+    let text = b"BZh9 = \"a bzip2-looking key\"\n";
+    let (content, detected) = read_decompressed(&text[..], None).unwrap();
+    assert_eq!((content.as_slice(), detected), (&text[..], None));
+
+    assert_eq!(
+        Compression::from_path(std::path::Path::new("config.yaml.zst")),
+        Some(Compression::Zstd)
+    );
+    assert!(read_decompressed(&b"not xz"[..], Some(Compression::Xz)).is_err());
+}
+
+#[test]
+fn test_sniff_text_format() {
+    let cases: [(&str, TextFormat); 7] = [
+        ("  {\"name\": \"web\"}\n", TextFormat::Json),
+        ("<?xml version=\"1.0\"?>\n<config/>\n", TextFormat::Xml),
+        ("apiVersion: v1\nkind: ConfigMap\n", TextFormat::Yaml),
+        ("- one\n- two\n", TextFormat::Yaml),
+        (
+            "[server]\nport = 8080\nhost = \"localhost\"\n",
+            TextFormat::Toml,
+        ),
+        ("; settings\n[server]\nhost=localhost\n", TextFormat::Ini),
+        ("id,name\n1,\"Smith, J\"\n2,Jones\n", TextFormat::Csv),
+    ];
+    for (content, format) in cases {
+        assert_eq!(
+            sniff_text_format(content.as_bytes()).unwrap(),
+            Some(format),
+            "{content:?}"
+        );
+    }
+
+    assert_eq!(sniff_text_format(b"").unwrap(), None);
+    assert_eq!(sniff_text_format(b"just some words\n").unwrap(), None);
+    let ambiguous = sniff_text_format(b"[servers]\n").unwrap_err();
+    assert_eq!(ambiguous.to_string(), "input could be YAML or TOML");
+}